@@ -0,0 +1,75 @@
+// --- Классификация причин отмены платежа ---
+// Подсказывает checkout'у, что показать пользователю и стоит ли предлагать повтор.
+
+use crate::{CancellationDetails, CancellationParty, CancellationReason};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CancellationCategory {
+    UserFixable,            // Пользователь может исправить данные и повторить тем же способом
+    RetryWithAnotherMethod, // Стоит предложить другой способ оплаты или другую карту
+    RetryLater,             // Временный сбой на стороне банка или YooKassa
+    DoNotRetry,             // Повторять нельзя (подозрение на мошенничество, отозванное разрешение)
+    MerchantSide,           // Отмена из-за действий или бездействия магазина
+}
+
+impl CancellationReason {
+    pub fn category(&self) -> CancellationCategory {
+        match self {
+            CancellationReason::ThreeDSecureFailed
+            | CancellationReason::InvalidCardNumber
+            | CancellationReason::InvalidCsc
+            | CancellationReason::ExpiredOnConfirmation => CancellationCategory::UserFixable,
+            CancellationReason::CallIssuer
+            | CancellationReason::CardExpired
+            | CancellationReason::CountryForbidden
+            | CancellationReason::GeneralDecline
+            | CancellationReason::IdentificationRequired
+            | CancellationReason::InsufficientFunds
+            | CancellationReason::PaymentMethodLimitExceeded
+            | CancellationReason::PaymentMethodRestricted
+            | CancellationReason::UnsupportedMobileOperator
+            | CancellationReason::Unknown(_) => CancellationCategory::RetryWithAnotherMethod,
+            CancellationReason::IssuerUnavailable | CancellationReason::InternalTimeout => {
+                CancellationCategory::RetryLater
+            }
+            CancellationReason::FraudSuspected | CancellationReason::PermissionRevoked => {
+                CancellationCategory::DoNotRetry
+            }
+            CancellationReason::CanceledByMerchant
+            | CancellationReason::ExpiredOnCapture
+            | CancellationReason::DealExpired => CancellationCategory::MerchantSide,
+        }
+    }
+
+    pub fn is_user_fixable(&self) -> bool {
+        self.category() == CancellationCategory::UserFixable
+    }
+
+    // Можно ли предложить пользователю оплатить еще раз (тем же или другим способом)
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self.category(),
+            CancellationCategory::UserFixable
+                | CancellationCategory::RetryWithAnotherMethod
+                | CancellationCategory::RetryLater
+        )
+    }
+
+    pub fn is_merchant_side(&self) -> bool {
+        self.category() == CancellationCategory::MerchantSide
+    }
+}
+
+impl CancellationDetails {
+    // Если отмену инициировал магазин, причина всегда относится к стороне магазина
+    pub fn category(&self) -> CancellationCategory {
+        match self.party {
+            CancellationParty::Merchant => CancellationCategory::MerchantSide,
+            _ => self.reason.category(),
+        }
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        self.party != CancellationParty::Merchant && self.reason.is_retryable()
+    }
+}
@@ -0,0 +1,321 @@
+// --- Запись и воспроизведение HTTP обмена с YooKassa ("кассеты") ---
+// RecordingTransport отправляет запросы в API и дописывает пары запрос/ответ в JSON файл,
+// ReplayTransport отвечает из этого файла без сети.
+// Заголовки (и с ними Authorization) не сохраняются, данные карты в телах маскируются.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use url::Url;
+
+use crate::YooKassaError;
+use crate::secret::mask_card_number;
+use crate::transport::{HttpRequest, HttpResponse, HttpTransport, TransportFuture};
+
+const REDACTED: &str = "***";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String, // Путь URL вместе с query, без схемы и хоста
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub body: Value,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, YooKassaError> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), YooKassaError> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("в кассете нет неиспользованной записи для {method} {path}")]
+pub struct UnmatchedRequestError {
+    pub method: String,
+    pub path: String,
+}
+
+// Транспорт-обертка: передает запросы во вложенный транспорт и дописывает каждую
+// пару запрос/ответ в кассету. Файл перезаписывается после каждого запроса,
+// чтобы кассета сохранилась даже при падении теста. Ошибка записи файла не отменяет
+// уже полученный ответ: она сохраняется и доступна через take_save_error().
+pub struct RecordingTransport {
+    inner: Arc<dyn HttpTransport>,
+    path: PathBuf,
+    cassette: Mutex<Cassette>,
+    save_error: Mutex<Option<YooKassaError>>,
+}
+
+impl RecordingTransport {
+    pub fn new(inner: Arc<dyn HttpTransport>, path: impl Into<PathBuf>) -> Self {
+        RecordingTransport {
+            inner,
+            path: path.into(),
+            cassette: Mutex::new(Cassette::default()),
+            save_error: Mutex::new(None),
+        }
+    }
+
+    // Последняя ошибка записи кассеты в файл, если была; после вызова сбрасывается
+    pub fn take_save_error(&self) -> Option<YooKassaError> {
+        lock(&self.save_error).take()
+    }
+
+    // Записать кассету в файл сейчас (например, повторно после take_save_error)
+    pub fn save(&self) -> Result<(), YooKassaError> {
+        lock(&self.cassette).save(&self.path)
+    }
+
+    fn record(&self, request: &HttpRequest, response: &HttpResponse) {
+        let interaction = Interaction {
+            request: recorded_request(request),
+            response: RecordedResponse {
+                status: response.status,
+                body: serde_json::from_str(&response.body)
+                    .map(|mut body| {
+                        redact(&mut body);
+                        body
+                    })
+                    .unwrap_or_else(|_| Value::String(response.body.clone())),
+            },
+        };
+        let mut cassette = lock(&self.cassette);
+        cassette.interactions.push(interaction);
+        if let Err(error) = cassette.save(&self.path) {
+            *lock(&self.save_error) = Some(error);
+        }
+    }
+}
+
+impl HttpTransport for RecordingTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let response = self.inner.send(request.clone()).await?;
+            self.record(&request, &response);
+            Ok(response)
+        })
+    }
+}
+
+// Транспорт, отвечающий из кассеты без обращения к сети: на запрос отдается первая
+// неиспользованная запись с тем же методом, путем и телом (ключ идемпотентности
+// не сравнивается). Запрос без подходящей записи завершается UnmatchedRequestError.
+pub struct ReplayTransport {
+    state: Mutex<ReplayState>,
+}
+
+struct ReplayState {
+    cassette: Cassette,
+    used: Vec<bool>, // Какие записи уже воспроизведены
+}
+
+impl ReplayTransport {
+    pub fn new(cassette: Cassette) -> Self {
+        let used = vec![false; cassette.interactions.len()];
+        ReplayTransport {
+            state: Mutex::new(ReplayState { cassette, used }),
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, YooKassaError> {
+        Ok(Self::new(Cassette::load(path)?))
+    }
+
+    // Сколько записей еще не было воспроизведено
+    pub fn remaining(&self) -> usize {
+        lock(&self.state).used.iter().filter(|used| !**used).count()
+    }
+
+    fn replay(&self, request: &HttpRequest) -> Result<HttpResponse, YooKassaError> {
+        let recorded = recorded_request(request);
+        let mut state = lock(&self.state);
+        let ReplayState { cassette, used } = &mut *state;
+        let position = cassette
+            .interactions
+            .iter()
+            .zip(used.iter())
+            .position(|(interaction, used)| !used && interaction.request == recorded)
+            .ok_or_else(|| UnmatchedRequestError {
+                method: recorded.method.clone(),
+                path: recorded.path.clone(),
+            })?;
+        used[position] = true;
+
+        let response = &cassette.interactions[position].response;
+        let body = match &response.body {
+            Value::String(raw) => raw.clone(),
+            body => body.to_string(),
+        };
+        Ok(HttpResponse {
+            status: response.status,
+            body,
+        })
+    }
+}
+
+impl HttpTransport for ReplayTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        Box::pin(std::future::ready(self.replay(&request)))
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+// Путь и query без схемы и хоста, чтобы кассету можно было воспроизвести
+// против другого адреса (например, mock::MockServer на случайном порту)
+fn recorded_request(request: &HttpRequest) -> RecordedRequest {
+    let path = match Url::parse(&request.url) {
+        Ok(url) => match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        },
+        Err(_) => request.url.clone(),
+    };
+    let body = request.body.as_ref().map(|body| {
+        let mut body = serde_json::from_slice(body)
+            .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(body).to_string()));
+        redact(&mut body);
+        body
+    });
+    RecordedRequest {
+        method: request.method.to_string(),
+        path,
+        body,
+    }
+}
+
+// Маскирует номер карты (см. secret::mask_card_number), CSC и платежные токены
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                match (key.as_str(), &*field) {
+                    ("csc" | "payment_token", Value::String(_)) => {
+                        *field = Value::String(REDACTED.to_string())
+                    }
+                    ("card", Value::Object(_)) => {
+                        if let Some(Value::String(number)) = field.get_mut("number") {
+                            *number = mask_card_number(number);
+                        }
+                        redact(field);
+                    }
+                    _ => redact(field),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn redact_masks_card_data_and_tokens() {
+        let mut body = json!({
+            "amount": { "value": "100.00", "currency": "RUB" },
+            "payment_token": "eyJ0eXBlIjoiY3J5cHRvZ3JhbSJ9",
+            "payment_method_data": {
+                "type": "bank_card",
+                "card": {
+                    "number": "4793128161644804",
+                    "expiry_year": "2030",
+                    "expiry_month": "12",
+                    "csc": "123",
+                },
+            },
+        });
+        redact(&mut body);
+        assert_eq!(body["payment_token"], REDACTED);
+        let card = &body["payment_method_data"]["card"];
+        assert_eq!(card["number"], "479312******4804");
+        assert_eq!(card["csc"], REDACTED);
+        assert_eq!(card["expiry_year"], "2030");
+        assert_eq!(body["amount"]["value"], "100.00");
+    }
+
+    #[test]
+    fn redact_walks_nested_arrays() {
+        let mut body = json!({
+            "items": [
+                { "card": { "number": "5555555555554444", "csc": "321" } },
+                [{ "payment_token": "token" }, { "csc": 123 }],
+            ],
+        });
+        redact(&mut body);
+        assert_eq!(body["items"][0]["card"]["number"], "555555******4444");
+        assert_eq!(body["items"][0]["card"]["csc"], REDACTED);
+        assert_eq!(body["items"][1][0]["payment_token"], REDACTED);
+        // Маскируются только строковые значения
+        assert_eq!(body["items"][1][1]["csc"], 123);
+    }
+
+    struct StaticTransport;
+
+    impl HttpTransport for StaticTransport {
+        fn send(&self, _request: HttpRequest) -> TransportFuture<'_> {
+            Box::pin(std::future::ready(Ok(HttpResponse {
+                status: 200,
+                body: r#"{"account_id":"100500"}"#.to_string(),
+            })))
+        }
+    }
+
+    #[tokio::test]
+    async fn save_error_does_not_fail_request() {
+        let path = std::env::temp_dir()
+            .join(format!("yookassa-{}", uuid::Uuid::new_v4()))
+            .join("cassette.json");
+        let transport = RecordingTransport::new(Arc::new(StaticTransport), &path);
+        let request = HttpRequest {
+            method: reqwest::Method::GET,
+            url: "https://api.yookassa.ru/v3/me".to_string(),
+            headers: Vec::new(),
+            authorization: None,
+            body: None,
+            timeout: None,
+        };
+
+        // Каталога нет: ответ возвращается, ошибка записи доступна отдельно
+        let response = transport.send(request).await.unwrap();
+        assert_eq!(response.status, 200);
+        assert!(transport.take_save_error().is_some());
+        assert!(transport.take_save_error().is_none());
+
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        transport.save().unwrap();
+        assert_eq!(Cassette::load(&path).unwrap().interactions.len(), 1);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
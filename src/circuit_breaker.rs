@@ -0,0 +1,414 @@
+// --- Circuit breaker для запросов к API ---
+// Пока доля неудачных запросов (сетевые ошибки, таймауты, 5xx) в последних window
+// запросах ниже порога, запросы идут как обычно (Closed). При превышении порога
+// цепь размыкается (Open) и запросы сразу завершаются CircuitOpenError, не дожидаясь
+// таймаута. Через open_duration пропускается несколько пробных запросов (HalfOpen):
+// если все успешны, цепь замыкается, при первой неудаче снова размыкается.
+//
+//     let breaker = Arc::new(CircuitBreaker::new(CircuitBreakerConfig::default()));
+//     breaker.on_state_change(|from, to| log::warn!("YooKassa: {from:?} -> {to:?}"));
+//     let client = YooKassaClient::builder(shop_id, secret_key)
+//         .circuit_breaker(breaker.clone())
+//         .build()?;
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::metrics_sink::MetricsSink;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CircuitBreakerConfig {
+    pub failure_rate_threshold: f64, // Доля неудач (0.0-1.0), при которой цепь размыкается
+    pub minimum_requests: usize,     // Меньше запросов в окне - порог не проверяется
+    pub window: usize,               // Сколько последних запросов учитывается
+    pub open_duration: Duration,     // Сколько цепь остается разомкнутой
+    pub half_open_requests: u32,     // Пробных запросов в состоянии HalfOpen
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        CircuitBreakerConfig {
+            failure_rate_threshold: 0.5,
+            minimum_requests: 10,
+            window: 20,
+            open_duration: Duration::from_secs(30),
+            half_open_requests: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "YooKassa API временно недоступен (circuit breaker разомкнут), повторите через {retry_after:?}"
+)]
+pub struct CircuitOpenError {
+    pub retry_after: Duration,
+}
+
+enum Inner {
+    Closed {
+        outcomes: VecDeque<bool>, // true - неудача
+    },
+    Open {
+        since: Instant,
+    },
+    HalfOpen {
+        since: Instant,
+        started: u32,
+        succeeded: u32,
+    },
+}
+
+impl Inner {
+    fn state(&self) -> CircuitState {
+        match self {
+            Inner::Closed { .. } => CircuitState::Closed,
+            Inner::Open { .. } => CircuitState::Open,
+            Inner::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+}
+
+type StateHook = Box<dyn Fn(CircuitState, CircuitState) + Send + Sync>;
+
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    inner: Mutex<Inner>,
+    hooks: RwLock<Vec<StateHook>>,
+    metrics: OnceLock<Arc<dyn MetricsSink>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        CircuitBreaker {
+            config,
+            inner: Mutex::new(Inner::Closed {
+                outcomes: VecDeque::new(),
+            }),
+            hooks: RwLock::new(Vec::new()),
+            metrics: OnceLock::new(),
+        }
+    }
+
+    // Получатель метрик смены состояния (circuit_state_changed). Подключается один раз:
+    // YooKassaClientBuilder::build подключает свои metrics, только если у breaker их еще нет,
+    // поэтому общий breaker не считает переходы несколько раз.
+    pub fn with_metrics(self, metrics: Arc<dyn MetricsSink>) -> Self {
+        self.attach_metrics(metrics);
+        self
+    }
+
+    pub(crate) fn attach_metrics(&self, metrics: Arc<dyn MetricsSink>) {
+        let _ = self.metrics.set(metrics);
+    }
+
+    // Вызывается при каждой смене состояния с (старое, новое), вне внутренних блокировок
+    pub fn on_state_change(
+        &self,
+        hook: impl Fn(CircuitState, CircuitState) + Send + Sync + 'static,
+    ) {
+        self.hooks
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(Box::new(hook));
+    }
+
+    pub fn state(&self) -> CircuitState {
+        self.lock().state()
+    }
+
+    // Разрешение на запрос. В состоянии Open (и когда пробные запросы HalfOpen исчерпаны)
+    // возвращает CircuitOpenError.
+    pub fn try_acquire(&self) -> Result<(), CircuitOpenError> {
+        let mut inner = self.lock();
+        let open_duration = self.config.open_duration;
+        let mut transition = None;
+        let result = match &mut *inner {
+            Inner::Closed { .. } => Ok(()),
+            Inner::Open { since } => {
+                let elapsed = since.elapsed();
+                if elapsed >= open_duration {
+                    *inner = Inner::HalfOpen {
+                        since: Instant::now(),
+                        started: 1,
+                        succeeded: 0,
+                    };
+                    transition = Some((CircuitState::Open, CircuitState::HalfOpen));
+                    Ok(())
+                } else {
+                    Err(CircuitOpenError {
+                        retry_after: open_duration - elapsed,
+                    })
+                }
+            }
+            Inner::HalfOpen { since, started, .. } => {
+                // Пробные запросы, не вернувшиеся за open_duration, считаются потерянными
+                if *started >= self.config.half_open_requests && since.elapsed() >= open_duration {
+                    *since = Instant::now();
+                    *started = 0;
+                }
+                if *started < self.config.half_open_requests {
+                    *started += 1;
+                    Ok(())
+                } else {
+                    Err(CircuitOpenError {
+                        retry_after: open_duration.saturating_sub(since.elapsed()),
+                    })
+                }
+            }
+        };
+        drop(inner);
+        self.notify(transition);
+        result
+    }
+
+    pub fn record_success(&self) {
+        self.record(false);
+    }
+
+    pub fn record_failure(&self) {
+        self.record(true);
+    }
+
+    // Считается ли ответ со статусом status неудачей для breaker (ошибки клиента - нет)
+    pub fn is_failure_status(status: u16) -> bool {
+        status >= 500
+    }
+
+    fn record(&self, failed: bool) {
+        let mut inner = self.lock();
+        let mut transition = None;
+        match &mut *inner {
+            Inner::Closed { outcomes } => {
+                outcomes.push_back(failed);
+                while outcomes.len() > self.config.window.max(1) {
+                    outcomes.pop_front();
+                }
+                let failures = outcomes.iter().filter(|failed| **failed).count();
+                if failed
+                    && outcomes.len() >= self.config.minimum_requests
+                    && failures as f64 / outcomes.len() as f64 >= self.config.failure_rate_threshold
+                {
+                    *inner = Inner::Open {
+                        since: Instant::now(),
+                    };
+                    transition = Some((CircuitState::Closed, CircuitState::Open));
+                }
+            }
+            Inner::Open { .. } => {} // Ответы на запросы, начатые до размыкания
+            Inner::HalfOpen { succeeded, .. } => {
+                if failed {
+                    *inner = Inner::Open {
+                        since: Instant::now(),
+                    };
+                    transition = Some((CircuitState::HalfOpen, CircuitState::Open));
+                } else {
+                    *succeeded += 1;
+                    if *succeeded >= self.config.half_open_requests {
+                        *inner = Inner::Closed {
+                            outcomes: VecDeque::new(),
+                        };
+                        transition = Some((CircuitState::HalfOpen, CircuitState::Closed));
+                    }
+                }
+            }
+        }
+        drop(inner);
+        self.notify(transition);
+    }
+
+    fn notify(&self, transition: Option<(CircuitState, CircuitState)>) {
+        if let Some((from, to)) = transition {
+            let hooks = self
+                .hooks
+                .read()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            for hook in hooks.iter() {
+                hook(from, to);
+            }
+            if let Some(metrics) = self.metrics.get() {
+                metrics.circuit_state_changed(from, to);
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPEN_DURATION: Duration = Duration::from_millis(50);
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new(CircuitBreakerConfig {
+            failure_rate_threshold: 0.5,
+            minimum_requests: 4,
+            window: 4,
+            open_duration: OPEN_DURATION,
+            half_open_requests: 2,
+        })
+    }
+
+    fn open(breaker: &CircuitBreaker) {
+        for _ in 0..4 {
+            breaker.try_acquire().unwrap();
+            breaker.record_failure();
+        }
+        assert_eq!(breaker.state(), CircuitState::Open);
+    }
+
+    #[test]
+    fn opens_when_failure_rate_reaches_threshold() {
+        let breaker = breaker();
+        // Меньше minimum_requests - порог не проверяется
+        for _ in 0..3 {
+            breaker.record_failure();
+        }
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        // Успех сам по себе цепь не размыкает
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        // Окно заполнено, порог проверяется на следующей неудаче: 3 из 4
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        let error = breaker.try_acquire().unwrap_err();
+        assert!(error.retry_after <= OPEN_DURATION);
+    }
+
+    #[test]
+    fn window_forgets_old_failures() {
+        let breaker = breaker();
+        for _ in 0..3 {
+            breaker.record_failure();
+        }
+        for _ in 0..4 {
+            breaker.record_success();
+        }
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        // В окне из 4 запросов 2 неудачи - ровно порог 0.5
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+    }
+
+    #[test]
+    fn half_open_closes_after_successful_probes() {
+        let breaker = breaker();
+        open(&breaker);
+        std::thread::sleep(OPEN_DURATION);
+
+        breaker.try_acquire().unwrap();
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        breaker.try_acquire().unwrap();
+        // Пробные запросы исчерпаны
+        assert!(breaker.try_acquire().is_err());
+
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        breaker.try_acquire().unwrap();
+    }
+
+    #[test]
+    fn half_open_reopens_on_failure() {
+        let breaker = breaker();
+        open(&breaker);
+        std::thread::sleep(OPEN_DURATION);
+
+        breaker.try_acquire().unwrap();
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(breaker.try_acquire().is_err());
+    }
+
+    #[test]
+    fn lost_probes_are_replaced_after_open_duration() {
+        let breaker = breaker();
+        open(&breaker);
+        std::thread::sleep(OPEN_DURATION);
+        breaker.try_acquire().unwrap();
+        breaker.try_acquire().unwrap();
+        assert!(breaker.try_acquire().is_err());
+
+        std::thread::sleep(OPEN_DURATION);
+        breaker.try_acquire().unwrap();
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+    }
+
+    #[test]
+    fn hooks_receive_every_transition() {
+        let breaker = breaker();
+        let transitions = Arc::new(Mutex::new(Vec::new()));
+        let recorded = transitions.clone();
+        breaker.on_state_change(move |from, to| recorded.lock().unwrap().push((from, to)));
+
+        open(&breaker);
+        std::thread::sleep(OPEN_DURATION);
+        breaker.try_acquire().unwrap();
+        breaker.record_failure();
+        std::thread::sleep(OPEN_DURATION);
+        breaker.try_acquire().unwrap();
+        breaker.try_acquire().unwrap();
+        breaker.record_success();
+        breaker.record_success();
+
+        use CircuitState::*;
+        assert_eq!(
+            *transitions.lock().unwrap(),
+            vec![
+                (Closed, Open),
+                (Open, HalfOpen),
+                (HalfOpen, Open),
+                (Open, HalfOpen),
+                (HalfOpen, Closed),
+            ]
+        );
+    }
+
+    #[test]
+    fn metrics_are_attached_once() {
+        #[derive(Default)]
+        struct Transitions(Mutex<u32>);
+
+        impl MetricsSink for Transitions {
+            fn circuit_state_changed(&self, _from: CircuitState, _to: CircuitState) {
+                *self.0.lock().unwrap() += 1;
+            }
+        }
+
+        let first = Arc::new(Transitions::default());
+        let second = Arc::new(Transitions::default());
+        let breaker = breaker().with_metrics(first.clone());
+        breaker.attach_metrics(first.clone());
+        breaker.attach_metrics(second.clone());
+
+        open(&breaker);
+        assert_eq!(*first.0.lock().unwrap(), 1);
+        assert_eq!(*second.0.lock().unwrap(), 0);
+    }
+
+    #[test]
+    fn only_server_errors_are_failures() {
+        assert!(!CircuitBreaker::is_failure_status(200));
+        assert!(!CircuitBreaker::is_failure_status(400));
+        assert!(!CircuitBreaker::is_failure_status(429));
+        assert!(CircuitBreaker::is_failure_status(500));
+        assert!(CircuitBreaker::is_failure_status(503));
+    }
+}
@@ -0,0 +1,373 @@
+// --- Конфигурация клиента из переменных окружения и файлов ---
+// YooKassaConfig десериализуется из любого формата serde (TOML, YAML, JSON):
+//
+//     shop_id = "123456"
+//     secret_key = "test_..."
+//     read_timeout_secs = 10
+//     max_retries = 3
+//
+// Переменные окружения: YOOKASSA_SHOP_ID, YOOKASSA_SECRET_KEY, YOOKASSA_BASE_URL,
+// YOOKASSA_CONNECT_TIMEOUT_SECS, YOOKASSA_TIMEOUT_SECS, YOOKASSA_READ_TIMEOUT_SECS,
+// YOOKASSA_WRITE_TIMEOUT_SECS, YOOKASSA_MAX_RETRIES, YOOKASSA_RETRY_BACKOFF_MS,
+// YOOKASSA_PROXY, YOOKASSA_USER_AGENT.
+
+use serde::Deserialize;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::{RetryPolicy, SecretString, YooKassaClientBuilder};
+
+const TEST_KEY_PREFIX: &str = "test_";
+const LIVE_KEY_PREFIX: &str = "live_";
+
+// Режим магазина по префиксу секретного ключа
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyMode {
+    Test, // test_... - тестовый магазин, деньги не списываются
+    Live, // live_... - боевой магазин
+}
+
+impl KeyMode {
+    pub fn of(secret_key: &str) -> Option<Self> {
+        if secret_key.starts_with(TEST_KEY_PREFIX) {
+            Some(KeyMode::Test)
+        } else if secret_key.starts_with(LIVE_KEY_PREFIX) {
+            Some(KeyMode::Live)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ConfigError {
+    #[error("не задан параметр {0}")]
+    Missing(&'static str),
+    #[error("некорректное значение {name}={value:?}: {reason}")]
+    Invalid {
+        name: &'static str,
+        value: String,
+        reason: String,
+    },
+    #[error("секретный ключ должен начинаться с test_ или live_")]
+    UnknownKeyMode,
+    #[error("ожидался ключ режима {expected:?}, а задан ключ режима {actual:?}")]
+    KeyModeMismatch { expected: KeyMode, actual: KeyMode },
+}
+
+// Неизвестные поля отклоняются, чтобы опечатка в имени (max_retry) не проходила молча
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct YooKassaConfig {
+    pub shop_id: String,
+    pub secret_key: SecretString,
+    #[serde(default)]
+    pub base_url: Option<String>,
+    #[serde(default)]
+    pub connect_timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>, // Для всех операций, если не задан read/write
+    #[serde(default)]
+    pub read_timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub write_timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub max_retries: Option<u32>,
+    #[serde(default)]
+    pub retry_backoff_ms: Option<u64>, // Пауза перед первым повтором; только вместе с max_retries
+    #[serde(default)]
+    pub proxy: Option<String>,
+    #[serde(default)]
+    pub user_agent: Option<String>,
+}
+
+impl YooKassaConfig {
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    // Как from_env, но значения берутся из var (удобно для тестов и своих источников)
+    pub fn from_vars(var: impl Fn(&str) -> Option<String>) -> Result<Self, ConfigError> {
+        let string = |name: &'static str| var(name).filter(|value| !value.trim().is_empty());
+        let config = YooKassaConfig {
+            shop_id: string("YOOKASSA_SHOP_ID").ok_or(ConfigError::Missing("YOOKASSA_SHOP_ID"))?,
+            secret_key: string("YOOKASSA_SECRET_KEY")
+                .ok_or(ConfigError::Missing("YOOKASSA_SECRET_KEY"))?
+                .into(),
+            base_url: string("YOOKASSA_BASE_URL"),
+            connect_timeout_secs: parse_var(&var, "YOOKASSA_CONNECT_TIMEOUT_SECS")?,
+            timeout_secs: parse_var(&var, "YOOKASSA_TIMEOUT_SECS")?,
+            read_timeout_secs: parse_var(&var, "YOOKASSA_READ_TIMEOUT_SECS")?,
+            write_timeout_secs: parse_var(&var, "YOOKASSA_WRITE_TIMEOUT_SECS")?,
+            max_retries: parse_var(&var, "YOOKASSA_MAX_RETRIES")?,
+            retry_backoff_ms: parse_var(&var, "YOOKASSA_RETRY_BACKOFF_MS")?,
+            proxy: string("YOOKASSA_PROXY"),
+            user_agent: string("YOOKASSA_USER_AGENT"),
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    // Проверка значений, пришедших из файла: serde не ловит пустые строки и нулевые таймауты
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.shop_id.trim().is_empty() {
+            return Err(ConfigError::Missing("shop_id"));
+        }
+        if !self.shop_id.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ConfigError::Invalid {
+                name: "shop_id",
+                value: self.shop_id.clone(),
+                reason: "ожидаются только цифры".to_string(),
+            });
+        }
+        if self.secret_key.expose_secret().trim().is_empty() {
+            return Err(ConfigError::Missing("secret_key"));
+        }
+        self.key_mode()?;
+
+        let timeouts = [
+            ("connect_timeout_secs", self.connect_timeout_secs),
+            ("timeout_secs", self.timeout_secs),
+            ("read_timeout_secs", self.read_timeout_secs),
+            ("write_timeout_secs", self.write_timeout_secs),
+        ];
+        for (name, timeout) in timeouts {
+            if timeout == Some(0) {
+                return Err(ConfigError::Invalid {
+                    name,
+                    value: "0".to_string(),
+                    reason: "таймаут должен быть больше нуля".to_string(),
+                });
+            }
+        }
+        // Без max_retries повторов нет, и пауза между ними ни на что не влияет
+        if let (Some(ms), None) = (self.retry_backoff_ms, self.max_retries) {
+            return Err(ConfigError::Invalid {
+                name: "retry_backoff_ms",
+                value: ms.to_string(),
+                reason: "задается только вместе с max_retries".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn key_mode(&self) -> Result<KeyMode, ConfigError> {
+        KeyMode::of(self.secret_key.expose_secret()).ok_or(ConfigError::UnknownKeyMode)
+    }
+
+    pub fn is_test(&self) -> bool {
+        self.key_mode() == Ok(KeyMode::Test)
+    }
+
+    // Защита от запуска тестового окружения с боевыми ключами и наоборот
+    pub fn require_key_mode(&self, expected: KeyMode) -> Result<(), ConfigError> {
+        let actual = self.key_mode()?;
+        if actual != expected {
+            return Err(ConfigError::KeyModeMismatch { expected, actual });
+        }
+        Ok(())
+    }
+
+    pub fn builder(&self) -> Result<YooKassaClientBuilder, ConfigError> {
+        self.validate()?;
+        let mut builder = YooKassaClientBuilder::new(self.shop_id.clone(), self.secret_key.clone());
+        if let Some(base_url) = &self.base_url {
+            builder = builder.base_url(base_url.clone());
+        }
+        if let Some(secs) = self.connect_timeout_secs {
+            builder = builder.connect_timeout(Duration::from_secs(secs));
+        }
+        if let Some(secs) = self.timeout_secs {
+            builder = builder.timeout(Duration::from_secs(secs));
+        }
+        if let Some(secs) = self.read_timeout_secs {
+            builder = builder.read_timeout(Duration::from_secs(secs));
+        }
+        if let Some(secs) = self.write_timeout_secs {
+            builder = builder.write_timeout(Duration::from_secs(secs));
+        }
+        if let Some(max_retries) = self.max_retries {
+            let mut retry_policy = RetryPolicy::new(max_retries);
+            if let Some(ms) = self.retry_backoff_ms {
+                retry_policy =
+                    retry_policy.set_backoff(Duration::from_millis(ms), retry_policy.max_backoff);
+            }
+            builder = builder.retry_policy(retry_policy);
+        }
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(user_agent.clone());
+        }
+        Ok(builder)
+    }
+}
+
+fn parse_var<T>(
+    var: &impl Fn(&str) -> Option<String>,
+    name: &'static str,
+) -> Result<Option<T>, ConfigError>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    match var(name).filter(|value| !value.trim().is_empty()) {
+        None => Ok(None),
+        Some(value) => {
+            value
+                .trim()
+                .parse()
+                .map(Some)
+                .map_err(|error: T::Err| ConfigError::Invalid {
+                    name,
+                    reason: error.to_string(),
+                    value,
+                })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn from_vars(vars: &[(&str, &str)]) -> Result<YooKassaConfig, ConfigError> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        YooKassaConfig::from_vars(|name| vars.get(name).cloned())
+    }
+
+    const REQUIRED: [(&str, &str); 2] = [
+        ("YOOKASSA_SHOP_ID", "100500"),
+        ("YOOKASSA_SECRET_KEY", "test_secret"),
+    ];
+
+    fn with_required(extra: &[(&'static str, &'static str)]) -> Vec<(&'static str, &'static str)> {
+        REQUIRED.iter().chain(extra).copied().collect()
+    }
+
+    #[test]
+    fn missing_variables() {
+        assert_eq!(
+            from_vars(&[]).unwrap_err(),
+            ConfigError::Missing("YOOKASSA_SHOP_ID")
+        );
+        assert_eq!(
+            from_vars(&[
+                ("YOOKASSA_SHOP_ID", "100500"),
+                ("YOOKASSA_SECRET_KEY", "  ")
+            ])
+            .unwrap_err(),
+            ConfigError::Missing("YOOKASSA_SECRET_KEY")
+        );
+    }
+
+    #[test]
+    fn bad_shop_id() {
+        let error = from_vars(&[
+            ("YOOKASSA_SHOP_ID", "shop-1"),
+            ("YOOKASSA_SECRET_KEY", "test_secret"),
+        ])
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            ConfigError::Invalid {
+                name: "shop_id",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn key_modes() {
+        let config = from_vars(&REQUIRED).unwrap();
+        assert_eq!(config.key_mode(), Ok(KeyMode::Test));
+        assert!(config.is_test());
+        assert_eq!(
+            config.require_key_mode(KeyMode::Live),
+            Err(ConfigError::KeyModeMismatch {
+                expected: KeyMode::Live,
+                actual: KeyMode::Test,
+            })
+        );
+
+        let config = from_vars(&[
+            ("YOOKASSA_SHOP_ID", "100500"),
+            ("YOOKASSA_SECRET_KEY", "live_secret"),
+        ])
+        .unwrap();
+        assert_eq!(config.require_key_mode(KeyMode::Live), Ok(()));
+        assert!(!config.is_test());
+
+        assert_eq!(
+            from_vars(&[
+                ("YOOKASSA_SHOP_ID", "100500"),
+                ("YOOKASSA_SECRET_KEY", "secret"),
+            ])
+            .unwrap_err(),
+            ConfigError::UnknownKeyMode
+        );
+    }
+
+    #[test]
+    fn timeouts() {
+        let config = from_vars(&with_required(&[
+            ("YOOKASSA_TIMEOUT_SECS", " 10 "),
+            ("YOOKASSA_READ_TIMEOUT_SECS", ""),
+        ]))
+        .unwrap();
+        assert_eq!(config.timeout_secs, Some(10));
+        assert_eq!(config.read_timeout_secs, None);
+
+        let error =
+            from_vars(&with_required(&[("YOOKASSA_CONNECT_TIMEOUT_SECS", "5s")])).unwrap_err();
+        assert!(matches!(
+            error,
+            ConfigError::Invalid { name: "YOOKASSA_CONNECT_TIMEOUT_SECS", ref value, .. }
+                if value == "5s"
+        ));
+
+        let error = from_vars(&with_required(&[("YOOKASSA_WRITE_TIMEOUT_SECS", "0")])).unwrap_err();
+        assert!(matches!(
+            error,
+            ConfigError::Invalid {
+                name: "write_timeout_secs",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn retry_backoff_requires_max_retries() {
+        let error = from_vars(&with_required(&[("YOOKASSA_RETRY_BACKOFF_MS", "200")])).unwrap_err();
+        assert!(matches!(
+            error,
+            ConfigError::Invalid {
+                name: "retry_backoff_ms",
+                ..
+            }
+        ));
+
+        let config = from_vars(&with_required(&[
+            ("YOOKASSA_MAX_RETRIES", "3"),
+            ("YOOKASSA_RETRY_BACKOFF_MS", "200"),
+        ]))
+        .unwrap();
+        assert_eq!(config.max_retries, Some(3));
+        assert_eq!(config.retry_backoff_ms, Some(200));
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        let result: Result<YooKassaConfig, _> = serde_json::from_value(serde_json::json!({
+            "shop_id": "100500",
+            "secret_key": "test_secret",
+            "max_retry": 3,
+        }));
+        assert!(result.unwrap_err().to_string().contains("max_retry"));
+    }
+}
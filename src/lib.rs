@@ -1,24 +1,170 @@
-use reqwest::header::{ACCEPT, CONTENT_TYPE, HeaderMap, HeaderValue};
-use reqwest::{Client as ReqwestClient, Method, Response};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use reqwest::Method;
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
 use uuid::Uuid;
 
+#[macro_use]
+mod macros;
+
+pub mod builder;
+pub mod cancellation;
+pub mod cassette;
+pub mod circuit_breaker;
+pub mod config;
+pub mod localize;
+pub mod marking;
+pub mod metrics_sink;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod oauth;
+pub mod payment_state;
+pub mod pool;
+pub mod rate_limit;
+pub mod receipt;
+pub mod sandbox;
+pub mod secret;
+mod telemetry;
+pub mod transport;
+pub mod webhook;
+
+pub use builder::{ClientBuildError, OperationClass, RetryPolicy, Timeouts, YooKassaClientBuilder};
+pub use cancellation::CancellationCategory;
+pub use cassette::{Cassette, RecordingTransport, ReplayTransport, UnmatchedRequestError};
+pub use circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitOpenError, CircuitState};
+pub use config::{ConfigError, KeyMode, YooKassaConfig};
+pub use localize::{Language, Localize};
+pub use marking::{MarkCodeInfo, Measure, PlannedStatus};
+#[cfg(feature = "metrics")]
+pub use metrics_sink::MetricsCrateSink;
+pub use metrics_sink::{MetricsSink, RequestMetrics, RequestOutcome};
+pub use oauth::{OAuthClient, OAuthToken};
+pub use payment_state::{PaymentOperation, PaymentStateError};
+pub use pool::{PoolError, YooKassaClientPool};
+pub use rate_limit::{EndpointGroup, RateLimit, RateLimiter};
+pub use receipt::{
+    AgentType, PaymentMode, PaymentSubject, ReceiptBuildError, ReceiptBuilder,
+    ReceiptValidationError, ReceiptViolation, SettlementReceiptError, TaxSystemCode, VatCode,
+};
+pub use secret::SecretString;
+pub use transport::{HttpRequest, HttpResponse, HttpTransport, NetworkError, TransportFuture};
+pub use webhook::{NotificationEvent, WebhookNotification};
+
+use metrics_sink::endpoint_template;
+use secret::mask_card_number;
+use telemetry::RequestTrace;
+use transport::{default_reqwest_client, is_network_error};
+
 pub type YooKassaError = Box<dyn std::error::Error + Send + Sync>;
 
+// Дата и время в формате ISO 8601 - строка в том виде, в каком пришла от API.
+// С feature "chrono" разбирается в chrono::DateTime<Utc> через to_chrono().
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct Timestamp(String);
+
+impl Timestamp {
+    pub fn new(value: impl Into<String>) -> Self {
+        Timestamp(value.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    #[cfg(feature = "chrono")]
+    pub fn to_chrono(&self) -> Result<chrono::DateTime<chrono::Utc>, chrono::ParseError> {
+        chrono::DateTime::parse_from_rfc3339(&self.0).map(|time| time.to_utc())
+    }
+}
+
+impl std::fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+// С feature "chrono" сравниваются моменты времени, а не строки: "12:00:00Z" и
+// "15:00:00+03:00" упорядочены как равные по времени и различаются только строкой.
+// Неразборчивые значения идут после разобранных и сравниваются как строки.
+impl Ord for Timestamp {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        #[cfg(feature = "chrono")]
+        match (self.to_chrono(), other.to_chrono()) {
+            (Ok(left), Ok(right)) => left.cmp(&right).then_with(|| self.0.cmp(&other.0)),
+            (Ok(_), Err(_)) => std::cmp::Ordering::Less,
+            (Err(_), Ok(_)) => std::cmp::Ordering::Greater,
+            (Err(_), Err(_)) => self.0.cmp(&other.0),
+        }
+        #[cfg(not(feature = "chrono"))]
+        self.0.cmp(&other.0)
+    }
+}
+
+impl PartialOrd for Timestamp {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Формат ответов API: "2024-01-01T12:00:00.000Z"
+#[cfg(feature = "chrono")]
+impl From<chrono::DateTime<chrono::Utc>> for Timestamp {
+    fn from(time: chrono::DateTime<chrono::Utc>) -> Self {
+        Timestamp(time.to_rfc3339_opts(chrono::SecondsFormat::Millis, true))
+    }
+}
+
 const YOOKASSA_API_BASE_URL: &str = "https://api.yookassa.ru/v3/";
 const IDEMPOTENCE_KEY_HEADER: &str = "Idempotence-Key";
 
+api_enum! {
+    // Код ошибки API
+    pub enum ApiErrorCode {
+        InvalidRequest => "invalid_request",
+        NotSupported => "not_supported",
+        InvalidCredentials => "invalid_credentials",
+        Forbidden => "forbidden",
+        NotFound => "not_found",
+        TooManyRequests => "too_many_requests",
+        InternalServerError => "internal_server_error",
+    }
+}
+
 // Структура для парсинга тела ошибки от API YooKassa (если оно есть)
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct YooKassaApiError {
     #[serde(rename = "type")]
     pub error_type: String, // Например, "error"
     pub id: String,          // Уникальный идентификатор ошибки
-    pub code: String,        // Код ошибки (например, "invalid_request")
+    pub code: ApiErrorCode,  // Код ошибки (например, InvalidRequest)
     pub description: String, // Описание ошибки для разработчика
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parameter: Option<String>, // Параметр, вызвавший ошибку
+    #[serde(flatten, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>, // Поля, неизвестные этой версии библиотеки
+}
+
+impl std::fmt::Display for YooKassaApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.code, self.description)?;
+        if let Some(parameter) = &self.parameter {
+            write!(f, " (параметр: {parameter})")?;
+        }
+        Ok(())
+    }
+}
+
+// Ошибка, которую возвращает клиент при неуспешном HTTP статусе ответа
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("YooKassa API вернул статус {status}: {message}")]
+pub struct ApiError {
+    pub status: u16,                             // HTTP статус ответа
+    pub message: String, // Описание ошибки из ответа или тело ответа как есть
+    pub error_details: Option<YooKassaApiError>, // Разобранное тело ошибки, если удалось
 }
 
 // Сумма
@@ -28,11 +174,52 @@ pub struct Amount {
     pub currency: String, // Код валюты (например, "RUB")
 }
 
+impl Amount {
+    pub fn new(value: impl Into<String>, currency: impl Into<String>) -> Self {
+        Amount {
+            value: value.into(),
+            currency: currency.into(),
+        }
+    }
+
+    // Сумма в копейках (минимальных единицах валюты). None, если value не в формате "123" / "123.4" / "123.45"
+    pub fn to_minor_units(&self) -> Option<i64> {
+        let (integer, fraction) = match self.value.split_once('.') {
+            Some((integer, fraction)) => (integer, fraction),
+            None => (self.value.as_str(), ""),
+        };
+        if integer.is_empty()
+            || fraction.len() > 2
+            || !integer.bytes().all(|b| b.is_ascii_digit())
+            || !fraction.bytes().all(|b| b.is_ascii_digit())
+            || (self.value.contains('.') && fraction.is_empty())
+        {
+            return None;
+        }
+        let integer: i64 = integer.parse().ok()?;
+        let fraction: i64 = match fraction.len() {
+            0 => 0,
+            1 => fraction.parse::<i64>().ok()? * 10,
+            _ => fraction.parse().ok()?,
+        };
+        integer.checked_mul(100)?.checked_add(fraction)
+    }
+
+    pub fn from_minor_units(minor_units: i64, currency: impl Into<String>) -> Self {
+        let sign = if minor_units < 0 { "-" } else { "" };
+        let abs = minor_units.unsigned_abs();
+        Amount {
+            value: format!("{sign}{}.{:02}", abs / 100, abs % 100),
+            currency: currency.into(),
+        }
+    }
+}
+
 // Данные для подтверждения платежа (в запросе)
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ConfirmationRequest {
     #[serde(rename = "type")]
-    pub confirmation_type: String, // Тип подтверждения ("redirect")
+    pub confirmation_type: ConfirmationType, // Тип подтверждения (Redirect)
     pub return_url: String, // URL для возврата пользователя
     #[serde(skip_serializing_if = "Option::is_none")]
     pub enforce: Option<bool>, // Для управления 3-D Secure
@@ -44,7 +231,7 @@ pub struct ConfirmationRequest {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PaymentMethodData {
     #[serde(rename = "type")]
-    pub payment_method_type: String, // Тип способа оплаты ("bank_card", "sbp", etc.)
+    pub payment_method_type: PaymentMethodType, // Тип способа оплаты (BankCard, Sbp, etc.)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub card: Option<CardData>, // Данные карты (только при PCI DSS!)
     // Другие поля для других способов оплаты (login для SberPay, phone для mobile_balance, etc.)
@@ -55,17 +242,30 @@ pub struct PaymentMethodData {
 }
 
 // Данные банковской карты (для запроса при PCI DSS - использовать с ОСТОРОЖНОСТЬЮ!)
-#[derive(Serialize, Deserialize, Debug, Clone)]
+// В Debug номер маскируется (см. secret::mask_card_number), CSC не выводится.
+#[derive(Serialize, Deserialize, Clone)]
 pub struct CardData {
-    pub number: String,
+    pub number: SecretString,
     pub expiry_year: String,
     pub expiry_month: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub csc: Option<String>, // CVC/CVV
+    pub csc: Option<SecretString>, // CVC/CVV
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cardholder: Option<String>, // Имя держателя карты
 }
 
+impl std::fmt::Debug for CardData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CardData")
+            .field("number", &mask_card_number(self.number.expose_secret()))
+            .field("expiry_year", &self.expiry_year)
+            .field("expiry_month", &self.expiry_month)
+            .field("csc", &self.csc)
+            .field("cardholder", &self.cardholder)
+            .finish()
+    }
+}
+
 // Запрос на создание платежа
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct CreatePaymentRequest {
@@ -103,23 +303,121 @@ pub struct CapturePaymentRequest {
                                   // ... другие поля по необходимости (transfers, deal, etc.)
 }
 
+// Запрос на создание возврата
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CreateRefundRequest {
+    pub payment_id: String,
+    pub amount: Amount, // Сумма возврата (может быть меньше суммы платежа)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>, // Комментарий к возврату
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub receipt: Option<Receipt>, // Чек возврата для 54-ФЗ
+}
+
 // --- Структуры ответа API ---
 
-// Статус платежа
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
-#[serde(rename_all = "snake_case")]
-pub enum PaymentStatus {
-    Pending,
-    WaitingForCapture,
-    Succeeded,
-    Canceled,
+api_enum! {
+    // Статус платежа
+    pub enum PaymentStatus {
+        Pending => "pending",
+        WaitingForCapture => "waiting_for_capture",
+        Succeeded => "succeeded",
+        Canceled => "canceled",
+    }
+}
+
+api_enum! {
+    // Сценарий подтверждения платежа
+    pub enum ConfirmationType {
+        Redirect => "redirect",
+        External => "external",
+        Qr => "qr",
+        Embedded => "embedded",
+        MobileApplication => "mobile_application",
+    }
+}
+
+api_enum! {
+    // Тип способа оплаты
+    pub enum PaymentMethodType {
+        BankCard => "bank_card",
+        YooMoney => "yoo_money",
+        Sbp => "sbp",
+        SberPay => "sberbank",
+        TinkoffBank => "tinkoff_bank",
+        SberLoan => "sber_loan",
+        SberBnpl => "sber_bnpl",
+        MobileBalance => "mobile_balance",
+        B2bSberbank => "b2b_sberbank",
+        ElectronicCertificate => "electronic_certificate",
+        Cash => "cash",
+        Installments => "installments",
+        Qiwi => "qiwi",
+        Webmoney => "webmoney",
+        Alfabank => "alfabank",
+        ApplePay => "apple_pay",
+        GooglePay => "google_pay",
+        Wechat => "wechat",
+    }
+}
+
+api_enum! {
+    // Участник процесса платежа, который принял решение об отмене
+    pub enum CancellationParty {
+        YooMoney => "yoo_money",
+        PaymentNetwork => "payment_network",
+        Merchant => "merchant",
+    }
+}
+
+api_enum! {
+    // Причина отмены платежа
+    pub enum CancellationReason {
+        ThreeDSecureFailed => "3d_secure_failed",
+        CallIssuer => "call_issuer",
+        CanceledByMerchant => "canceled_by_merchant",
+        CardExpired => "card_expired",
+        CountryForbidden => "country_forbidden",
+        DealExpired => "deal_expired",
+        ExpiredOnCapture => "expired_on_capture",
+        ExpiredOnConfirmation => "expired_on_confirmation",
+        FraudSuspected => "fraud_suspected",
+        GeneralDecline => "general_decline",
+        IdentificationRequired => "identification_required",
+        InsufficientFunds => "insufficient_funds",
+        InternalTimeout => "internal_timeout",
+        InvalidCardNumber => "invalid_card_number",
+        InvalidCsc => "invalid_csc",
+        IssuerUnavailable => "issuer_unavailable",
+        PaymentMethodLimitExceeded => "payment_method_limit_exceeded",
+        PaymentMethodRestricted => "payment_method_restricted",
+        PermissionRevoked => "permission_revoked",
+        UnsupportedMobileOperator => "unsupported_mobile_operator",
+    }
+}
+
+api_enum! {
+    // Статус доставки данных для чека в онлайн-кассу
+    pub enum ReceiptRegistrationStatus {
+        Pending => "pending",
+        Succeeded => "succeeded",
+        Canceled => "canceled",
+    }
+}
+
+api_enum! {
+    // Статус магазина или шлюза
+    pub enum ShopStatus {
+        Enabled => "enabled",
+        Disabled => "disabled",
+    }
 }
 
 // Детали подтверждения (в ответе)
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ConfirmationResponse {
     #[serde(rename = "type")]
-    pub confirmation_type: String, // "redirect", "external", "qr", "embedded", "mobile_application"
+    pub confirmation_type: ConfirmationType,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub confirmation_url: Option<String>, // URL для редиректа пользователя
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -130,6 +428,8 @@ pub struct ConfirmationResponse {
     pub locale: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub confirmation_data: Option<String>, // Для QR кода
+    #[serde(flatten, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>, // Поля, неизвестные этой версии библиотеки
 }
 
 // Получатель платежа
@@ -137,6 +437,8 @@ pub struct ConfirmationResponse {
 pub struct Recipient {
     pub account_id: String,
     pub gateway_id: String,
+    #[serde(flatten, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>, // Поля, неизвестные этой версии библиотеки
 }
 
 // Данные карты в ответе
@@ -155,6 +457,8 @@ pub struct CardDetails {
     pub source: Option<String>, // "mir_pay", etc.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub card_product: Option<CardProduct>,
+    #[serde(flatten, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>, // Поля, неизвестные этой версии библиотеки
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -167,7 +471,7 @@ pub struct CardProduct {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PaymentMethod {
     #[serde(rename = "type")]
-    pub payment_method_type: String, // "bank_card", "yoo_money", "sbp", etc.
+    pub payment_method_type: PaymentMethodType,
     pub id: String,
     pub saved: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -183,6 +487,8 @@ pub struct PaymentMethod {
     pub sbp_operation_id: Option<String>, // Для SBP
     #[serde(skip_serializing_if = "Option::is_none")]
     pub payer_bank_details: Option<PayerBankDetails>, // Для SBP
+    #[serde(flatten, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>, // Поля, неизвестные этой версии библиотеки
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -194,8 +500,10 @@ pub struct PayerBankDetails {
 // Детали отмены
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CancellationDetails {
-    pub party: String,  // "yookassa", "merchant", "payment_network"
-    pub reason: String, // "expired_on_confirmation", "payment_rejected", etc.
+    pub party: CancellationParty,
+    pub reason: CancellationReason,
+    #[serde(flatten, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>, // Поля, неизвестные этой версии библиотеки
 }
 
 // 3-D Secure
@@ -204,7 +512,8 @@ pub struct ThreeDSecure {
     pub applied: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub method_relevant: Option<bool>,
-    // ... другие возможные поля
+    #[serde(flatten, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>, // Поля, неизвестные этой версии библиотеки
 }
 
 // Детали авторизации
@@ -216,6 +525,8 @@ pub struct AuthorizationDetails {
     pub auth_code: Option<String>, // Код авторизации
     #[serde(skip_serializing_if = "Option::is_none")]
     pub three_d_secure: Option<ThreeDSecure>,
+    #[serde(flatten, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>, // Поля, неизвестные этой версии библиотеки
 }
 
 // Полный объект платежа (ответ)
@@ -232,10 +543,10 @@ pub struct Payment {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub payment_method: Option<PaymentMethod>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub captured_at: Option<String>, // ISO 8601 timestamp
-    pub created_at: String, // ISO 8601 timestamp
+    pub captured_at: Option<Timestamp>,
+    pub created_at: Timestamp,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub expires_at: Option<String>, // ISO 8601 timestamp (для waiting_for_capture)
+    pub expires_at: Option<Timestamp>, // Срок подтверждения (для waiting_for_capture)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub confirmation: Option<ConfirmationResponse>,
     pub test: bool,       // Тестовый платеж?
@@ -244,14 +555,51 @@ pub struct Payment {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub refunded_amount: Option<Amount>, // Сумма возвращенных средств
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub receipt_registration: Option<String>, // Статус регистрации чека ("pending", "succeeded", "canceled")
+    pub receipt_registration: Option<ReceiptRegistrationStatus>, // Статус регистрации чека
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cancellation_details: Option<CancellationDetails>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub authorization_details: Option<AuthorizationDetails>,
-    // ... другие поля (transfers, deal, merchant_customer_id, etc.)
+    // ... другие поля (transfers, deal, merchant_customer_id, etc.) попадают в extra
+    #[serde(flatten, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>, // Поля, неизвестные этой версии библиотеки
+}
+
+api_enum! {
+    // Статус возврата
+    pub enum RefundStatus {
+        Pending => "pending",
+        Succeeded => "succeeded",
+        Canceled => "canceled",
+    }
+}
+
+// Возврат (ответ)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Refund {
+    pub id: String,
+    pub payment_id: String,
+    pub status: RefundStatus,
+    pub amount: Amount,
+    pub created_at: Timestamp,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub receipt_registration: Option<ReceiptRegistrationStatus>,
+    #[serde(flatten, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>, // Поля, неизвестные этой версии библиотеки
+}
+
+// Список возвратов (ответ)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RefundList {
+    #[serde(rename = "type")]
+    pub list_type: String, // "list"
+    pub items: Vec<Refund>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
 // Список платежей (ответ)
@@ -262,6 +610,58 @@ pub struct PaymentList {
     pub items: Vec<Payment>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub next_cursor: Option<String>, // Указатель для пагинации
+    #[serde(flatten, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>, // Поля, неизвестные этой версии библиотеки
+}
+
+// Настройки магазина или шлюза (ответ на GET /me)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Me {
+    pub account_id: String, // Идентификатор магазина или шлюза
+    pub status: ShopStatus,
+    pub test: bool, // Тестовый магазин?
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fiscalization_enabled: Option<bool>, // Подключена ли отправка чеков (устаревшее поле)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fiscalization: Option<Fiscalization>, // Настройки фискализации
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_methods: Option<Vec<PaymentMethodType>>, // Подключенные способы оплаты
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub itn: Option<String>, // ИНН магазина
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payout_methods: Option<Vec<String>>, // Для шлюзов выплат
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>, // Название шлюза (для выплат)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payout_balance: Option<Amount>, // Баланс шлюза (для выплат)
+    #[serde(flatten, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>, // Поля, неизвестные этой версии библиотеки
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Fiscalization {
+    pub enabled: bool,
+    pub provider: String, // "atol", "avanpost", "business_ru", etc.
+}
+
+impl Me {
+    pub fn is_enabled(&self) -> bool {
+        self.status == ShopStatus::Enabled
+    }
+
+    // Учитывает и новое поле fiscalization, и устаревшее fiscalization_enabled
+    pub fn is_fiscalization_enabled(&self) -> bool {
+        match &self.fiscalization {
+            Some(fiscalization) => fiscalization.enabled,
+            None => self.fiscalization_enabled.unwrap_or(false),
+        }
+    }
+
+    pub fn supports_payment_method(&self, payment_method_type: &str) -> bool {
+        self.payment_methods
+            .as_deref()
+            .is_some_and(|methods| methods.iter().any(|m| m.as_str() == payment_method_type))
+    }
 }
 
 // --- Структуры для чеков 54-ФЗ ---
@@ -281,12 +681,12 @@ pub struct ReceiptCustomer {
 pub struct ReceiptItem {
     pub description: String, // Наименование товара/услуги
     pub quantity: String,    // Количество/объем (строка)
-    pub amount: Amount,      // Стоимость товара с учетом количества и скидок
-    pub vat_code: i32,       // Ставка НДС (см. документацию YooKassa)
+    pub amount: Amount,      // Цена за единицу с учетом скидок (итог позиции = amount × quantity)
+    pub vat_code: VatCode,   // Ставка НДС
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub payment_mode: Option<String>, // Признак способа расчета
+    pub payment_mode: Option<PaymentMode>, // Признак способа расчета
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub payment_subject: Option<String>, // Признак предмета расчета
+    pub payment_subject: Option<PaymentSubject>, // Признак предмета расчета
     #[serde(skip_serializing_if = "Option::is_none")]
     pub country_of_origin_code: Option<String>, // Код страны происхождения товара (ISO 3166-1 alpha-2)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -301,7 +701,30 @@ pub struct ReceiptItem {
     pub payment_subject_industry_details: Option<Vec<PaymentSubjectIndustryDetails>>, // Отраслевой реквизит предмета расчета
     #[serde(skip_serializing_if = "Option::is_none")]
     pub product_mark: Option<String>, // Код маркировки товара (для ФФД 1.2)
-                                      // ... другие поля для чеков
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mark_code_info: Option<MarkCodeInfo>, // Код товара (ФФД 1.2)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mark_mode: Option<String>, // Режим обработки кода маркировки, всегда "0"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub measure: Option<Measure>, // Мера количества (ФФД 1.2)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub planned_status: Option<PlannedStatus>, // Планируемый статус товара (ФФД 1.2)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supplier: Option<ReceiptSupplier>, // Поставщик товара (для агентской схемы)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_type: Option<AgentType>, // Тип посредника
+                                       // ... другие поля для чеков
+}
+
+// Поставщик товара или услуги при продаже по агентской схеме
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReceiptSupplier {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>, // Наименование поставщика
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>, // В формате ITU-T E.164
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inn: Option<String>, // ИНН поставщика (10 или 12 цифр)
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -324,13 +747,93 @@ pub struct Receipt {
     pub customer: Option<ReceiptCustomer>,
     pub items: Vec<ReceiptItem>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub tax_system_code: Option<i32>, // Код системы налогообложения
+    pub tax_system_code: Option<TaxSystemCode>, // Код системы налогообложения
     #[serde(skip_serializing_if = "Option::is_none")]
     pub receipt_industry_details: Option<Vec<ReceiptIndustryDetails>>, // Отраслевой реквизит чека
     #[serde(skip_serializing_if = "Option::is_none")]
     pub receipt_operational_details: Option<ReceiptOperationalDetails>, // Операционный реквизит чека
 }
 
+api_enum! {
+    // Тип чека
+    pub enum ReceiptType {
+        Payment => "payment",
+        Refund => "refund",
+    }
+}
+
+api_enum! {
+    // Тип расчета в чеке (settlements)
+    pub enum SettlementType {
+        Cashless => "cashless",           // Безналичный расчет
+        Prepayment => "prepayment",       // Предоплата (зачет аванса)
+        Postpayment => "postpayment",     // Постоплата (кредит)
+        Consideration => "consideration", // Встречное предоставление
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Settlement {
+    #[serde(rename = "type")]
+    pub settlement_type: SettlementType,
+    pub amount: Amount,
+}
+
+// Запрос на создание отдельного чека (POST /receipts), например чека зачета предоплаты
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateReceiptRequest {
+    #[serde(rename = "type")]
+    pub receipt_type: ReceiptType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_id: Option<String>, // Платеж, к которому относится чек
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refund_id: Option<String>, // Возврат, к которому относится чек
+    pub customer: ReceiptCustomer,
+    pub items: Vec<ReceiptItem>,
+    pub send: bool, // Сразу отправить чек покупателю (всегда true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tax_system_code: Option<TaxSystemCode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub receipt_industry_details: Option<Vec<ReceiptIndustryDetails>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub receipt_operational_details: Option<ReceiptOperationalDetails>,
+    pub settlements: Vec<Settlement>, // Перечень совершенных расчетов
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_behalf_of: Option<String>, // Для партнеров: магазин, от имени которого создается чек
+}
+
+// Чек, зарегистрированный через API (ответ /receipts)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FiscalReceipt {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub receipt_type: ReceiptType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refund_id: Option<String>,
+    pub status: ReceiptRegistrationStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fiscal_document_number: Option<String>, // Номер фискального документа
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fiscal_storage_number: Option<String>, // Номер фискального накопителя
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fiscal_attribute: Option<String>, // Фискальный признак документа
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registered_at: Option<Timestamp>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fiscal_provider_id: Option<String>,
+    pub items: Vec<ReceiptItem>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settlements: Option<Vec<Settlement>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tax_system_code: Option<TaxSystemCode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_behalf_of: Option<String>,
+    #[serde(flatten, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>, // Поля, неизвестные этой версии библиотеки
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ReceiptIndustryDetails {
     pub federal_id: String,      // 001-008
@@ -341,31 +844,97 @@ pub struct ReceiptIndustryDetails {
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ReceiptOperationalDetails {
-    pub operation_id: i32,  // от 0 до 255
-    pub value: String,      // до 64 символов
-    pub created_at: String, // ISO 8601 timestamp
+    pub operation_id: i32, // от 0 до 255
+    pub value: String,     // до 64 символов
+    pub created_at: Timestamp,
 }
 
 // --- Клиент YooKassa ---
 
+// Способ аутентификации запросов к API
+#[derive(Clone)]
+enum Credentials {
+    Basic {
+        shop_id: String,
+        secret_key: SecretString,
+    },
+    OAuth(OAuthToken),
+}
+
 #[derive(Clone)]
 pub struct YooKassaClient {
-    client: ReqwestClient,
-    shop_id: String,
-    secret_key: String,
+    transport: Arc<dyn HttpTransport>,
+    credentials: Credentials,
     base_url: String,
+    validate_receipts: bool,
+    timeouts: Timeouts,
+    retry_policy: RetryPolicy,
+    default_headers: Vec<(String, String)>, // User-Agent и заголовки из builder
+    rate_limiter: Option<Arc<RateLimiter>>, // Общий для всех клонов клиента
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
+    metrics: Option<Arc<dyn MetricsSink>>,
 }
 
 impl YooKassaClient {
-    pub fn new(shop_id: String, secret_key: String) -> Self {
-        YooKassaClient {
-            client: ReqwestClient::builder()
-                .timeout(Duration::from_secs(30))
-                .build()
-                .expect("Не удалось создать HTTP клиент"),
+    pub fn builder(shop_id: String, secret_key: impl Into<SecretString>) -> YooKassaClientBuilder {
+        YooKassaClientBuilder::new(shop_id, secret_key)
+    }
+
+    // Клиент из переменных окружения YOOKASSA_* (см. config::YooKassaConfig::from_env)
+    pub fn from_env() -> Result<Self, YooKassaError> {
+        Self::from_config(&YooKassaConfig::from_env()?)
+    }
+
+    pub fn from_config(config: &YooKassaConfig) -> Result<Self, YooKassaError> {
+        config.builder()?.build()
+    }
+
+    pub fn new(
+        shop_id: String,
+        secret_key: impl Into<SecretString>,
+    ) -> Result<Self, YooKassaError> {
+        Ok(Self::with_transport(
             shop_id,
             secret_key,
+            default_reqwest_client()?,
+        ))
+    }
+
+    // Клиент для работы от имени магазина, подключенного через OAuth
+    pub fn with_oauth_token(token: OAuthToken) -> Result<Self, YooKassaError> {
+        Ok(Self::from_parts(
+            Credentials::OAuth(token),
+            Arc::new(default_reqwest_client()?),
+        ))
+    }
+
+    // Клиент со своим транспортом: настроенный reqwest::Client, hyper, тестовая заглушка
+    pub fn with_transport(
+        shop_id: String,
+        secret_key: impl Into<SecretString>,
+        transport: impl HttpTransport + 'static,
+    ) -> Self {
+        Self::from_parts(
+            Credentials::Basic {
+                shop_id,
+                secret_key: secret_key.into(),
+            },
+            Arc::new(transport),
+        )
+    }
+
+    fn from_parts(credentials: Credentials, transport: Arc<dyn HttpTransport>) -> Self {
+        YooKassaClient {
+            transport,
+            credentials,
             base_url: YOOKASSA_API_BASE_URL.to_string(),
+            validate_receipts: false,
+            timeouts: Timeouts::default(),
+            retry_policy: RetryPolicy::none(),
+            default_headers: Vec::new(),
+            rate_limiter: None,
+            circuit_breaker: None,
+            metrics: None,
         }
     }
 
@@ -374,55 +943,205 @@ impl YooKassaClient {
         self
     }
 
+    pub fn set_transport(mut self, transport: impl HttpTransport + 'static) -> Self {
+        self.transport = Arc::new(transport);
+        self
+    }
+
+    // Проверять чек через Receipt::validate_against перед отправкой create_payment
+    pub fn set_receipt_validation(mut self, enabled: bool) -> Self {
+        self.validate_receipts = enabled;
+        self
+    }
+
+    // Отправлять запросы через текущий транспорт и записывать каждую пару запрос/ответ
+    // в кассету по пути path (см. cassette::RecordingTransport)
+    pub fn record_cassette(mut self, path: impl Into<PathBuf>) -> Self {
+        self.transport = Arc::new(RecordingTransport::new(self.transport, path));
+        self
+    }
+
+    // Отвечать из ранее записанной кассеты, не обращаясь к сети.
+    // Запрос, которого нет в кассете, завершается ошибкой UnmatchedRequestError.
+    pub fn replay_cassette(self, path: impl AsRef<Path>) -> Result<Self, YooKassaError> {
+        Ok(self.set_transport(ReplayTransport::load(path)?))
+    }
+
+    // Тестовый или боевой магазин; None для OAuth и ключей без префикса test_/live_
+    pub fn key_mode(&self) -> Option<KeyMode> {
+        match &self.credentials {
+            Credentials::Basic { secret_key, .. } => KeyMode::of(secret_key.expose_secret()),
+            Credentials::OAuth(_) => None,
+        }
+    }
+
+    // Строки собираются в буферах нужного размера, без переаллокаций, поэтому копий
+    // ключа в памяти не остается: промежуточные значения затираются при удалении
+    fn authorization_header(&self) -> SecretString {
+        match &self.credentials {
+            Credentials::Basic {
+                shop_id,
+                secret_key,
+            } => {
+                let secret_key = secret_key.expose_secret();
+                let mut credentials = String::with_capacity(shop_id.len() + 1 + secret_key.len());
+                credentials.push_str(shop_id);
+                credentials.push(':');
+                credentials.push_str(secret_key);
+                let credentials = SecretString::new(credentials);
+                let credentials = credentials.expose_secret().as_bytes();
+                let mut header = String::with_capacity(6 + credentials.len().div_ceil(3) * 4);
+                header.push_str("Basic ");
+                BASE64.encode_string(credentials, &mut header);
+                SecretString::new(header)
+            }
+            Credentials::OAuth(token) => {
+                let token = token.access_token.expose_secret();
+                let mut header = String::with_capacity(7 + token.len());
+                header.push_str("Bearer ");
+                header.push_str(token);
+                SecretString::new(header)
+            }
+        }
+    }
+
     async fn send_request<T: Serialize>(
         &self,
         method: Method,
         endpoint: &str,
         body: Option<&T>,
         idempotency_key_required: bool,
-    ) -> Result<Response, YooKassaError> {
-        let url = format!("{}{}", self.base_url, endpoint);
-        let mut headers = HeaderMap::new();
-        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
-        headers.insert(ACCEPT, HeaderValue::from_static("application/json")); // Явно указываем, что ждем JSON
-
-        if idempotency_key_required {
-            let idempotency_key = Uuid::new_v4().to_string();
-            headers.insert(
-                IDEMPOTENCE_KEY_HEADER,
-                HeaderValue::from_str(&idempotency_key)?,
-            );
+    ) -> Result<HttpResponse, YooKassaError> {
+        let mut headers = self.default_headers.clone();
+        headers.push(("Accept".to_string(), "application/json".to_string())); // Явно указываем, что ждем JSON
+        if body.is_some() {
+            headers.push(("Content-Type".to_string(), "application/json".to_string()));
+        }
+        let idempotency_key = idempotency_key_required.then(|| Uuid::new_v4().to_string());
+        if let Some(idempotency_key) = &idempotency_key {
+            headers.push((IDEMPOTENCE_KEY_HEADER.to_string(), idempotency_key.clone()));
         }
 
-        let mut request_builder = self
-            .client
-            .request(method, url)
-            .basic_auth(&self.shop_id, Some(&self.secret_key))
-            .headers(headers);
+        let trace = RequestTrace::start(&method, endpoint, idempotency_key.as_deref());
+        let request = HttpRequest {
+            timeout: self.timeouts.for_class(OperationClass::of(&method)),
+            method,
+            url: format!("{}{}", self.base_url, endpoint),
+            headers,
+            authorization: Some(self.authorization_header()),
+            body: body.map(serde_json::to_vec).transpose()?,
+        };
 
-        if let Some(payload) = body {
-            request_builder = request_builder.json(payload);
+        let started = Instant::now();
+        let endpoint_template = endpoint_template(endpoint);
+        let (attempts, result) = trace
+            .instrument(self.send_with_retries(
+                &request,
+                EndpointGroup::of(endpoint),
+                &endpoint_template,
+                &trace,
+            ))
+            .await;
+        trace.finish(attempts, &result);
+        if let Some(metrics) = &self.metrics {
+            let error_details = match &result {
+                Ok(response) if !response.is_success() => {
+                    serde_json::from_str::<YooKassaApiError>(&response.body).ok()
+                }
+                _ => None,
+            };
+            metrics.request_completed(&RequestMetrics {
+                endpoint: &endpoint_template,
+                method: request.method.as_str(),
+                outcome: RequestOutcome::of(&result),
+                error_code: error_details.as_ref().map(|details| &details.code),
+                latency: started.elapsed(),
+                attempts,
+            });
         }
+        result
+    }
 
-        let response = request_builder.send().await?;
+    // Ключ идемпотентности сформирован один раз, повторы отправляют тот же запрос.
+    // Возвращает число выполненных попыток вместе с результатом.
+    async fn send_with_retries(
+        &self,
+        request: &HttpRequest,
+        group: EndpointGroup,
+        endpoint_template: &str,
+        trace: &RequestTrace,
+    ) -> (u32, Result<HttpResponse, YooKassaError>) {
+        let mut attempt = 0;
+        loop {
+            // При разомкнутой цепи запрос завершается сразу, без повторов
+            if let Some(circuit_breaker) = &self.circuit_breaker
+                && let Err(error) = circuit_breaker.try_acquire()
+            {
+                return (attempt, Err(error.into()));
+            }
+            attempt += 1;
+            let started = Instant::now();
+            let result = match &self.rate_limiter {
+                Some(rate_limiter) => {
+                    let _permit = rate_limiter.acquire(group).await;
+                    self.transport.send(request.clone()).await
+                }
+                None => self.transport.send(request.clone()).await,
+            };
+            trace.attempt_finished(attempt, started.elapsed(), &result);
+            // Ошибки, не связанные с сетью (нет записи в кассете, некорректный заголовок),
+            // не повторяются и не влияют на circuit breaker
+            let network_error = matches!(&result, Err(error) if is_network_error(error));
+            if let Some(circuit_breaker) = &self.circuit_breaker {
+                match &result {
+                    Ok(response) if !CircuitBreaker::is_failure_status(response.status) => {
+                        circuit_breaker.record_success()
+                    }
+                    Ok(_) => circuit_breaker.record_failure(),
+                    Err(_) if network_error => circuit_breaker.record_failure(),
+                    Err(_) => {}
+                }
+            }
+            let retryable = match &result {
+                Ok(response) => RetryPolicy::is_retryable_status(response.status),
+                Err(_) => network_error,
+            };
+            if !retryable || attempt > self.retry_policy.max_retries {
+                return (attempt, result);
+            }
+            if let Some(metrics) = &self.metrics {
+                metrics.request_retried(endpoint_template, request.method.as_str(), attempt);
+            }
+            tokio::time::sleep(self.retry_policy.backoff(attempt - 1)).await;
+        }
+    }
 
-        Ok(response)
+    // Разбор входящего уведомления с учетом в метриках (webhook_received)
+    pub fn parse_notification(&self, body: &str) -> Result<WebhookNotification, YooKassaError> {
+        match &self.metrics {
+            Some(metrics) => WebhookNotification::from_json_with_metrics(body, metrics.as_ref()),
+            None => WebhookNotification::from_json(body),
+        }
     }
 
-    async fn process_response<R: for<'de> Deserialize<'de>>(
+    fn process_response<R: for<'de> Deserialize<'de>>(
         &self,
-        response: Response,
+        response: HttpResponse,
     ) -> Result<R, YooKassaError> {
-        let status = response.status();
-        if status.is_success() {
-            Ok(response.json::<R>().await?)
+        if response.is_success() {
+            Ok(serde_json::from_str::<R>(&response.body)?)
         } else {
-            let body_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Не удалось прочитать тело ответа".to_string());
-            let api_error_details: Option<YooKassaApiError> = serde_json::from_str(&body_text).ok();
-            Err("err".into())
+            let error_details: Option<YooKassaApiError> = serde_json::from_str(&response.body).ok();
+            let message = match &error_details {
+                Some(details) => details.to_string(),
+                None => response.body,
+            };
+            Err(ApiError {
+                status: response.status,
+                message,
+                error_details,
+            }
+            .into())
         }
     }
 
@@ -430,6 +1149,11 @@ impl YooKassaClient {
         &self,
         request: &CreatePaymentRequest,
     ) -> Result<Payment, YooKassaError> {
+        if self.validate_receipts
+            && let Some(receipt) = &request.receipt
+        {
+            receipt.validate_against(&request.amount)?;
+        }
         let response = self
             .send_request(
                 Method::POST,
@@ -438,7 +1162,7 @@ impl YooKassaClient {
                 true, // Требуется ключ идемпотентности
             )
             .await?;
-        self.process_response(response).await
+        self.process_response(response)
     }
 
     pub async fn get_payment(&self, payment_id: &str) -> Result<Payment, YooKassaError> {
@@ -449,7 +1173,7 @@ impl YooKassaClient {
             None,
             false // Не требуется ключ идемпотентности
         ).await?;
-        self.process_response(response).await
+        self.process_response(response)
     }
 
     pub async fn capture_payment(
@@ -469,7 +1193,7 @@ impl YooKassaClient {
                 true, // Требуется ключ идемпотентности
             )
             .await?;
-        self.process_response(response).await
+        self.process_response(response)
     }
 
     pub async fn cancel_payment(&self, payment_id: &str) -> Result<Payment, YooKassaError> {
@@ -478,26 +1202,79 @@ impl YooKassaClient {
         let response = self
             .send_request(Method::POST, &endpoint, Some(&empty_body), true)
             .await?;
-        self.process_response(response).await
+        self.process_response(response)
     }
 
     pub async fn list_payments(
         &self,
         params: Option<&[(&str, &str)]>,
     ) -> Result<PaymentList, YooKassaError> {
-        let url = format!("{}payments", self.base_url);
-        let mut request_builder = self
-            .client
-            .get(url)
-            .basic_auth(&self.shop_id, Some(&self.secret_key))
-            .header(ACCEPT, HeaderValue::from_static("application/json"));
-
+        let mut endpoint = "payments".to_string();
         if let Some(query_params) = params {
-            request_builder = request_builder.query(query_params);
+            let query = url::form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(query_params)
+                .finish();
+            endpoint = format!("{endpoint}?{query}");
         }
+        let response = self
+            .send_request::<()>(Method::GET, &endpoint, None, false)
+            .await?;
+        self.process_response(response)
+    }
+
+    pub async fn create_refund(
+        &self,
+        request: &CreateRefundRequest,
+    ) -> Result<Refund, YooKassaError> {
+        let response = self
+            .send_request(Method::POST, "refunds", Some(request), true)
+            .await?;
+        self.process_response(response)
+    }
+
+    pub async fn get_refund(&self, refund_id: &str) -> Result<Refund, YooKassaError> {
+        let endpoint = format!("refunds/{refund_id}");
+        let response = self
+            .send_request::<()>(Method::GET, &endpoint, None, false)
+            .await?;
+        self.process_response(response)
+    }
+
+    pub async fn create_receipt(
+        &self,
+        request: &CreateReceiptRequest,
+    ) -> Result<FiscalReceipt, YooKassaError> {
+        let response = self
+            .send_request(Method::POST, "receipts", Some(request), true)
+            .await?;
+        self.process_response(response)
+    }
+
+    pub async fn get_receipt(&self, receipt_id: &str) -> Result<FiscalReceipt, YooKassaError> {
+        let endpoint = format!("receipts/{receipt_id}");
+        let response = self
+            .send_request::<()>(Method::GET, &endpoint, None, false)
+            .await?;
+        self.process_response(response)
+    }
+
+    pub async fn get_me(&self) -> Result<Me, YooKassaError> {
+        let response = self
+            .send_request::<()>(Method::GET, "me", None, false)
+            .await?;
+        self.process_response(response)
+    }
 
-        let response = request_builder.send().await?;
-        self.process_response(response).await
+    // Настройки конкретного магазина при работе через OAuth (партнерская программа)
+    pub async fn get_me_on_behalf_of(&self, shop_id: &str) -> Result<Me, YooKassaError> {
+        let query = url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("on_behalf_of", shop_id)
+            .finish();
+        let endpoint = format!("me?{query}");
+        let response = self
+            .send_request::<()>(Method::GET, &endpoint, None, false)
+            .await?;
+        self.process_response(response)
     }
 }
 
@@ -508,11 +1285,12 @@ impl YooKassaClient {
 // async fn main() -> Result<(), Box<dyn std::error::Error>> {
 //     // ВАЖНО: Никогда не храните ключи прямо в коде в реальных приложениях!
 //     // Используйте переменные окружения, конфигурационные файлы или секрет-менеджеры.
-//     let shop_id = std::env::var("YOOKASSA_SHOP_ID").expect("Нужно установить YOOKASSA_SHOP_ID");
-//     let secret_key = std::env::var("YOOKASSA_SECRET_KEY").expect("Нужно установить YOOKASSA_SECRET_KEY");
+//     // Нужны YOOKASSA_SHOP_ID и YOOKASSA_SECRET_KEY, остальное - по желанию (см. config.rs)
+//     let config = YooKassaConfig::from_env()?;
 //     // Убедитесь, что используете ТЕСТОВЫЕ ключи для тестирования!
+//     config.require_key_mode(KeyMode::Test)?;
 
-//     let client = YooKassaClient::new(shop_id, secret_key);
+//     let client = YooKassaClient::from_config(&config)?;
 
 //     // 1. Создание платежа
 //     println!("Создание платежа...");
@@ -522,7 +1300,7 @@ impl YooKassaClient {
 //             currency: "RUB".to_string(),
 //         },
 //         confirmation: Some(ConfirmationRequest {
-//             confirmation_type: "redirect".to_string(),
+//             confirmation_type: ConfirmationType::Redirect,
 //             // Укажите ваш реальный URL для возврата
 //             return_url: "https://www.example.com/return_url".to_string(),
 //             enforce: None,
@@ -586,7 +1364,7 @@ impl YooKassaClient {
 //         }
 //         Err(e) => {
 //             eprintln!("Ошибка при создании платежа: {}", e);
-//             if let YooKassaError::ApiError { status: _, message: _, error_details: Some(details) } = e {
+//             if let Some(ApiError { error_details: Some(details), .. }) = e.downcast_ref::<ApiError>() {
 //                  eprintln!("Детали ошибки API: {:#?}", details);
 //             }
 //         }
@@ -610,3 +1388,69 @@ impl YooKassaClient {
 
 //     Ok(())
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn timestamp_to_chrono() {
+        use chrono::{TimeZone, Utc};
+
+        let expected = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        assert_eq!(
+            Timestamp::new("2024-01-01T12:00:00.000Z").to_chrono(),
+            Ok(expected)
+        );
+        assert_eq!(
+            Timestamp::new("2024-01-01T15:00:00+03:00").to_chrono(),
+            Ok(expected)
+        );
+        assert!(Timestamp::new("01.01.2024 12:00").to_chrono().is_err());
+        assert_eq!(
+            Timestamp::from(expected).as_str(),
+            "2024-01-01T12:00:00.000Z"
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn timestamp_ordering_uses_instant() {
+        let utc = Timestamp::new("2024-01-01T12:00:00.000Z");
+        let moscow = Timestamp::new("2024-01-01T14:30:00+03:00");
+        // Строкой "14:30" больше "12:00", но по времени это 11:30 UTC
+        assert!(moscow < utc);
+
+        let invalid = Timestamp::new("not a date");
+        let mut sorted = vec![invalid.clone(), utc.clone(), moscow.clone()];
+        sorted.sort();
+        assert_eq!(sorted, [moscow, utc, invalid]);
+    }
+
+    #[cfg(not(feature = "chrono"))]
+    #[test]
+    fn timestamp_ordering_uses_string() {
+        assert!(
+            Timestamp::new("2024-01-01T11:00:00.000Z") < Timestamp::new("2024-01-01T12:00:00.000Z")
+        );
+    }
+
+    #[test]
+    fn card_data_debug_masks_number_and_csc() {
+        let card = CardData {
+            number: "4793128161644804".into(),
+            expiry_year: "2030".to_string(),
+            expiry_month: "12".to_string(),
+            csc: Some("123".into()),
+            cardholder: Some("IVAN IVANOV".to_string()),
+        };
+        let debug = format!("{card:?}");
+        assert_eq!(
+            debug,
+            "CardData { number: \"479312******4804\", expiry_year: \"2030\", \
+             expiry_month: \"12\", csc: Some(SecretString(***)), \
+             cardholder: Some(\"IVAN IVANOV\") }"
+        );
+    }
+}
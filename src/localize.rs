@@ -0,0 +1,240 @@
+// --- Тексты для покупателя на русском и английском ---
+
+use crate::{
+    ApiError, ApiErrorCode, CancellationDetails, CancellationReason, CircuitOpenError,
+    YooKassaApiError,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Language {
+    #[default]
+    Ru,
+    En,
+}
+
+impl Language {
+    // По локали платежной формы ("ru_RU", "en_US"); все, кроме английского, - русский
+    pub fn from_locale(locale: &str) -> Self {
+        if locale.to_ascii_lowercase().starts_with("en") {
+            Language::En
+        } else {
+            Language::Ru
+        }
+    }
+}
+
+pub trait Localize {
+    fn localize(&self, language: Language) -> &'static str;
+}
+
+impl Localize for CancellationReason {
+    fn localize(&self, language: Language) -> &'static str {
+        let (ru, en) = match self {
+            CancellationReason::ThreeDSecureFailed => (
+                "Не пройдена аутентификация 3-D Secure. Попробуйте еще раз или обратитесь в банк.",
+                "3-D Secure authentication failed. Please try again or contact your bank.",
+            ),
+            CancellationReason::CallIssuer => (
+                "Банк отклонил платеж. Обратитесь в банк, выпустивший карту.",
+                "The payment was declined by your bank. Please contact the card issuer.",
+            ),
+            CancellationReason::CanceledByMerchant => (
+                "Платеж отменен магазином.",
+                "The payment was canceled by the merchant.",
+            ),
+            CancellationReason::CardExpired => (
+                "Истек срок действия карты. Используйте другую карту.",
+                "The card has expired. Please use another card.",
+            ),
+            CancellationReason::CountryForbidden => (
+                "Оплата картой, выпущенной в этой стране, недоступна.",
+                "Payments with cards issued in this country are not accepted.",
+            ),
+            CancellationReason::DealExpired => {
+                ("Истек срок действия сделки.", "The deal has expired.")
+            }
+            CancellationReason::ExpiredOnCapture => (
+                "Истек срок подтверждения платежа магазином.",
+                "The merchant did not confirm the payment in time.",
+            ),
+            CancellationReason::ExpiredOnConfirmation => (
+                "Платеж не был подтвержден вовремя. Попробуйте оплатить еще раз.",
+                "The payment was not confirmed in time. Please try again.",
+            ),
+            CancellationReason::FraudSuspected => (
+                "Платеж заблокирован из-за подозрения в мошенничестве.",
+                "The payment was blocked due to suspected fraud.",
+            ),
+            CancellationReason::GeneralDecline => (
+                "Платеж отклонен. Попробуйте другой способ оплаты.",
+                "The payment was declined. Please try another payment method.",
+            ),
+            CancellationReason::IdentificationRequired => (
+                "Превышен лимит для неидентифицированного кошелька. Пройдите идентификацию или выберите другой способ оплаты.",
+                "The limit for an unidentified wallet is exceeded. Please complete identification or choose another payment method.",
+            ),
+            CancellationReason::InsufficientFunds => (
+                "Недостаточно средств. Пополните счет или используйте другой способ оплаты.",
+                "Insufficient funds. Please top up or use another payment method.",
+            ),
+            CancellationReason::InternalTimeout => (
+                "Технический сбой. Попробуйте оплатить позже.",
+                "A technical error occurred. Please try again later.",
+            ),
+            CancellationReason::InvalidCardNumber => (
+                "Неверный номер карты. Проверьте данные и попробуйте еще раз.",
+                "Invalid card number. Please check the details and try again.",
+            ),
+            CancellationReason::InvalidCsc => (
+                "Неверный код CVV2 (CVC2, CVP2). Проверьте данные и попробуйте еще раз.",
+                "Invalid CVV2 (CVC2, CVP2) code. Please check the details and try again.",
+            ),
+            CancellationReason::IssuerUnavailable => (
+                "Банк временно недоступен. Попробуйте оплатить позже.",
+                "The issuing bank is temporarily unavailable. Please try again later.",
+            ),
+            CancellationReason::PaymentMethodLimitExceeded => (
+                "Исчерпан лимит платежей для этого способа оплаты.",
+                "The payment limit for this payment method has been exceeded.",
+            ),
+            CancellationReason::PaymentMethodRestricted => (
+                "Операции этим способом оплаты запрещены.",
+                "Payments with this payment method are restricted.",
+            ),
+            CancellationReason::PermissionRevoked => (
+                "Разрешение на автоплатежи отозвано.",
+                "Permission for recurring payments has been revoked.",
+            ),
+            CancellationReason::UnsupportedMobileOperator => (
+                "Оплата с номера этого мобильного оператора недоступна.",
+                "Payments from this mobile operator are not supported.",
+            ),
+            CancellationReason::Unknown(_) => (
+                "Платеж не прошел. Попробуйте еще раз или выберите другой способ оплаты.",
+                "The payment failed. Please try again or choose another payment method.",
+            ),
+        };
+        match language {
+            Language::Ru => ru,
+            Language::En => en,
+        }
+    }
+}
+
+impl Localize for CancellationDetails {
+    fn localize(&self, language: Language) -> &'static str {
+        self.reason.localize(language)
+    }
+}
+
+impl Localize for ApiErrorCode {
+    fn localize(&self, language: Language) -> &'static str {
+        let (ru, en) = match self {
+            ApiErrorCode::InvalidRequest => (
+                "Некорректный запрос к платежной системе.",
+                "Invalid request to the payment system.",
+            ),
+            ApiErrorCode::NotSupported => (
+                "Операция не поддерживается.",
+                "The operation is not supported.",
+            ),
+            ApiErrorCode::InvalidCredentials => (
+                "Ошибка авторизации в платежной системе.",
+                "Payment system authorization failed.",
+            ),
+            ApiErrorCode::Forbidden => (
+                "Операция запрещена для этого магазина.",
+                "The operation is forbidden for this shop.",
+            ),
+            ApiErrorCode::NotFound => (
+                "Запрошенный объект не найден.",
+                "The requested object was not found.",
+            ),
+            ApiErrorCode::TooManyRequests => (
+                "Слишком много запросов. Попробуйте позже.",
+                "Too many requests. Please try again later.",
+            ),
+            ApiErrorCode::InternalServerError | ApiErrorCode::Unknown(_) => (
+                "Платежная система временно недоступна. Попробуйте позже.",
+                "The payment system is temporarily unavailable. Please try again later.",
+            ),
+        };
+        match language {
+            Language::Ru => ru,
+            Language::En => en,
+        }
+    }
+}
+
+impl Localize for YooKassaApiError {
+    fn localize(&self, language: Language) -> &'static str {
+        self.code.localize(language)
+    }
+}
+
+impl Localize for ApiError {
+    fn localize(&self, language: Language) -> &'static str {
+        match &self.error_details {
+            Some(details) => details.localize(language),
+            None => code_for_status(self.status).localize(language),
+        }
+    }
+}
+
+// Тело ошибки не разобрано - текст выбирается по HTTP статусу: 4xx - ошибка запроса,
+// а не "временно недоступна", которая подходит только для 429 и 5xx
+fn code_for_status(status: u16) -> ApiErrorCode {
+    match status {
+        401 => ApiErrorCode::InvalidCredentials,
+        403 => ApiErrorCode::Forbidden,
+        404 => ApiErrorCode::NotFound,
+        405 | 501 => ApiErrorCode::NotSupported,
+        429 => ApiErrorCode::TooManyRequests,
+        400..=499 => ApiErrorCode::InvalidRequest,
+        _ => ApiErrorCode::InternalServerError,
+    }
+}
+
+// Запасное сообщение, пока circuit breaker разомкнут
+impl Localize for CircuitOpenError {
+    fn localize(&self, language: Language) -> &'static str {
+        ApiErrorCode::InternalServerError.localize(language)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_error(status: u16) -> ApiError {
+        ApiError {
+            status,
+            message: "<html>bad gateway</html>".to_string(),
+            error_details: None,
+        }
+    }
+
+    #[test]
+    fn api_error_without_details_uses_status() {
+        assert_eq!(
+            api_error(400).localize(Language::En),
+            "Invalid request to the payment system."
+        );
+        assert_eq!(
+            api_error(422).localize(Language::En),
+            "Invalid request to the payment system."
+        );
+        assert_eq!(
+            api_error(401).localize(Language::Ru),
+            "Ошибка авторизации в платежной системе."
+        );
+        assert_eq!(
+            api_error(429).localize(Language::En),
+            "Too many requests. Please try again later."
+        );
+        assert_eq!(
+            api_error(502).localize(Language::En),
+            "The payment system is temporarily unavailable. Please try again later."
+        );
+    }
+}
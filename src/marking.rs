@@ -0,0 +1,261 @@
+// --- Маркированные товары (ФФД 1.2, "Честный знак") ---
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde::{Deserialize, Serialize};
+
+// Разделитель групп GS1 (FNC1 / ASCII 29)
+const GS: char = '\u{1d}';
+// Префиксы идентификатора символики, которые добавляют некоторые сканеры
+const SYMBOLOGY_IDENTIFIERS: [&str; 3] = ["]d2", "]C1", "]Q3"];
+// Единственное допустимое значение mark_mode
+pub const MARK_MODE_DEFAULT: &str = "0";
+
+// Код товара (тег 1163). Заполняется ровно одно поле.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct MarkCodeInfo {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mark_code_raw: Option<String>, // Код в том виде, в котором он был прочитан сканером
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unknown: Option<String>, // Нераспознанный код товара
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ean_8: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ean_13: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub itf_14: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gs_10: Option<String>, // GS1 DataBar (base64)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gs_1m: Option<String>, // GS1 DataMatrix (base64)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub short: Option<String>, // Код маркировки в сокращенном формате (base64)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fur: Option<String>, // Контрольно-идентификационный знак мехового изделия
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub egais_20: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub egais_30: Option<String>,
+}
+
+impl MarkCodeInfo {
+    // Из скана DataMatrix "Честного знака": проверяет структуру GS1 и заполняет gs_1m
+    pub fn from_datamatrix(raw: &str) -> Result<Self, MarkCodeError> {
+        let code = Gs1DataMatrix::parse(raw)?;
+        Ok(MarkCodeInfo {
+            gs_1m: Some(BASE64.encode(code.normalized())),
+            ..Default::default()
+        })
+    }
+
+    // Код как есть, без разбора (YooKassa определит формат сама)
+    pub fn raw(raw: impl Into<String>) -> Self {
+        MarkCodeInfo {
+            mark_code_raw: Some(raw.into()),
+            ..Default::default()
+        }
+    }
+
+    // Сколько полей заполнено; для корректного объекта - ровно одно
+    pub fn filled_fields(&self) -> usize {
+        [
+            &self.mark_code_raw,
+            &self.unknown,
+            &self.ean_8,
+            &self.ean_13,
+            &self.itf_14,
+            &self.gs_10,
+            &self.gs_1m,
+            &self.short,
+            &self.fur,
+            &self.egais_20,
+            &self.egais_30,
+        ]
+        .into_iter()
+        .filter(|field| field.is_some())
+        .count()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MarkCodeError {
+    #[error("пустой код маркировки")]
+    Empty,
+    #[error("код маркировки не начинается с GTIN (AI 01)")]
+    MissingGtin,
+    #[error("некорректный GTIN {0:?}: ожидается 14 цифр")]
+    InvalidGtin(String),
+    #[error("в коде маркировки нет серийного номера (AI 21)")]
+    MissingSerial,
+}
+
+// Разобранный код GS1 DataMatrix: 01<GTIN>21<серийный номер><GS>91<ключ><GS>92<подпись>...
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gs1DataMatrix {
+    pub gtin: String,
+    pub serial: String,
+    pub tail: Vec<String>, // Остальные группы (91, 92, 93, ...) без разделителей
+}
+
+impl Gs1DataMatrix {
+    pub fn parse(raw: &str) -> Result<Self, MarkCodeError> {
+        let mut data = raw.trim_end_matches(['\r', '\n']);
+        for identifier in SYMBOLOGY_IDENTIFIERS {
+            if let Some(rest) = data.strip_prefix(identifier) {
+                data = rest;
+                break;
+            }
+        }
+        let data = data.trim_start_matches(GS);
+        if data.is_empty() {
+            return Err(MarkCodeError::Empty);
+        }
+
+        let rest = data.strip_prefix("01").ok_or(MarkCodeError::MissingGtin)?;
+        let gtin: String = rest.chars().take(14).collect();
+        if gtin.len() != 14 || !gtin.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(MarkCodeError::InvalidGtin(gtin));
+        }
+        let rest = rest[14..]
+            .strip_prefix("21")
+            .ok_or(MarkCodeError::MissingSerial)?;
+
+        let mut groups = rest.split(GS);
+        let serial = groups.next().unwrap_or_default().to_string();
+        if serial.is_empty() {
+            return Err(MarkCodeError::MissingSerial);
+        }
+        Ok(Gs1DataMatrix {
+            gtin,
+            serial,
+            tail: groups
+                .filter(|group| !group.is_empty())
+                .map(str::to_string)
+                .collect(),
+        })
+    }
+
+    // Код без идентификатора символики, группы разделены GS
+    pub fn normalized(&self) -> String {
+        let mut code = format!("01{}21{}", self.gtin, self.serial);
+        for group in &self.tail {
+            code.push(GS);
+            code.push_str(group);
+        }
+        code
+    }
+}
+
+api_enum! {
+    // Мера количества предмета расчета (measure)
+    pub enum Measure {
+        Piece => "piece",
+        Gram => "gram",
+        Kilogram => "kilogram",
+        Ton => "ton",
+        Centimeter => "centimeter",
+        Decimeter => "decimeter",
+        Meter => "meter",
+        SquareCentimeter => "square_centimeter",
+        SquareDecimeter => "square_decimeter",
+        SquareMeter => "square_meter",
+        Milliliter => "milliliter",
+        Liter => "liter",
+        CubicMeter => "cubic_meter",
+        KilowattHour => "kilowatt_hour",
+        Gigacalorie => "gigacalorie",
+        Day => "day",
+        Hour => "hour",
+        Minute => "minute",
+        Second => "second",
+        Kilobyte => "kilobyte",
+        Megabyte => "megabyte",
+        Gigabyte => "gigabyte",
+        Terabyte => "terabyte",
+        Another => "another",
+    }
+}
+
+api_int_enum! {
+    // Планируемый статус товара (planned_status)
+    pub enum PlannedStatus {
+        PieceSold => 1,         // Штучный товар реализован
+        MeasuredSold => 2,      // Мерный товар в стадии реализации
+        PieceReturned => 3,     // Штучный товар возвращен
+        MeasuredReturned => 4,  // Часть товара возвращена
+        StatusUnchanged => 5,   // Статус товара не изменился
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODE: &str = "010460780959150821sSBmxTYIFT(eq\u{1d}91FFD0\u{1d}92testtesttest";
+
+    #[test]
+    fn parses_gtin_serial_and_tail() {
+        let code = Gs1DataMatrix::parse(CODE).unwrap();
+        assert_eq!(code.gtin, "04607809591508");
+        assert_eq!(code.serial, "sSBmxTYIFT(eq");
+        assert_eq!(code.tail, vec!["91FFD0", "92testtesttest"]);
+        assert_eq!(code.normalized(), CODE);
+    }
+
+    #[test]
+    fn strips_symbology_identifier_and_line_ending() {
+        for identifier in SYMBOLOGY_IDENTIFIERS {
+            let raw = format!("{identifier}\u{1d}{CODE}\r\n");
+            assert_eq!(Gs1DataMatrix::parse(&raw).unwrap().normalized(), CODE);
+        }
+    }
+
+    #[test]
+    fn code_without_tail() {
+        let code = Gs1DataMatrix::parse("010460780959150821ABC123").unwrap();
+        assert_eq!(code.serial, "ABC123");
+        assert!(code.tail.is_empty());
+    }
+
+    #[test]
+    fn rejects_malformed_codes() {
+        assert_eq!(Gs1DataMatrix::parse(""), Err(MarkCodeError::Empty));
+        assert_eq!(Gs1DataMatrix::parse("]d2\r\n"), Err(MarkCodeError::Empty));
+        assert_eq!(
+            Gs1DataMatrix::parse("020460780959150821ABC"),
+            Err(MarkCodeError::MissingGtin)
+        );
+        assert_eq!(
+            Gs1DataMatrix::parse("0104607809591508"),
+            Err(MarkCodeError::MissingSerial)
+        );
+        assert_eq!(
+            Gs1DataMatrix::parse("010460780959"),
+            Err(MarkCodeError::InvalidGtin("0460780959".to_string()))
+        );
+        assert_eq!(
+            Gs1DataMatrix::parse("01046078095915AB21ABC"),
+            Err(MarkCodeError::InvalidGtin("046078095915AB".to_string()))
+        );
+        assert_eq!(
+            Gs1DataMatrix::parse("010460780959150822ABC"),
+            Err(MarkCodeError::MissingSerial)
+        );
+        assert_eq!(
+            Gs1DataMatrix::parse("010460780959150821\u{1d}91FFD0"),
+            Err(MarkCodeError::MissingSerial)
+        );
+        // Многобайтовые символы на месте GTIN не приводят к панике
+        assert!(matches!(
+            Gs1DataMatrix::parse("01ЧестныйЗнак2021ABC"),
+            Err(MarkCodeError::InvalidGtin(_))
+        ));
+    }
+
+    #[test]
+    fn from_datamatrix_fills_only_gs_1m() {
+        let info = MarkCodeInfo::from_datamatrix(&format!("]C1{CODE}")).unwrap();
+        assert_eq!(info.filled_fields(), 1);
+        assert_eq!(info.gs_1m, Some(BASE64.encode(CODE)));
+    }
+}
@@ -0,0 +1,137 @@
+// --- Метрики клиента ---
+// MetricsSink получает события клиента: завершенные запросы, повторы, смену состояния
+// circuit breaker и входящие уведомления. Методы по умолчанию пустые - реализуйте нужные.
+// С feature "metrics" есть готовая реализация MetricsCrateSink поверх крейта metrics
+// (экспорт в Prometheus - через metrics-exporter-prometheus в приложении).
+//
+// Метки специально с низкой кардинальностью: endpoint - шаблон пути без идентификаторов
+// ("payments/{id}/capture"), без query.
+
+use std::time::Duration;
+
+use crate::circuit_breaker::CircuitState;
+use crate::transport::HttpResponse;
+use crate::webhook::NotificationEvent;
+use crate::{ApiErrorCode, CircuitOpenError, YooKassaError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestOutcome {
+    Response(u16),  // HTTP статус ответа
+    TransportError, // Сеть, таймаут, TLS
+    CircuitOpen,    // Запрос не отправлен: circuit breaker разомкнут
+}
+
+impl RequestOutcome {
+    pub(crate) fn of(result: &Result<HttpResponse, YooKassaError>) -> Self {
+        match result {
+            Ok(response) => RequestOutcome::Response(response.status),
+            Err(error) if error.is::<CircuitOpenError>() => RequestOutcome::CircuitOpen,
+            Err(_) => RequestOutcome::TransportError,
+        }
+    }
+
+    // Значение метки status: код ответа, "transport_error" или "circuit_open"
+    pub fn label(&self) -> String {
+        match self {
+            RequestOutcome::Response(status) => status.to_string(),
+            RequestOutcome::TransportError => "transport_error".to_string(),
+            RequestOutcome::CircuitOpen => "circuit_open".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RequestMetrics<'a> {
+    pub endpoint: &'a str, // Шаблон пути, например "payments/{id}"
+    pub method: &'a str,
+    pub outcome: RequestOutcome,
+    pub error_code: Option<&'a ApiErrorCode>, // Для ответов с ошибкой API
+    pub latency: Duration,                    // С учетом повторов и пауз между ними
+    pub attempts: u32,
+}
+
+pub trait MetricsSink: Send + Sync {
+    fn request_completed(&self, _request: &RequestMetrics<'_>) {}
+
+    // Перед повтором номер attempt + 1
+    fn request_retried(&self, _endpoint: &str, _method: &str, _attempt: u32) {}
+
+    fn circuit_state_changed(&self, _from: CircuitState, _to: CircuitState) {}
+
+    fn webhook_received(&self, _event: &NotificationEvent) {}
+}
+
+// Шаблон пути: второй сегмент (идентификатор объекта) заменяется на {id}
+pub fn endpoint_template(endpoint: &str) -> String {
+    let path = endpoint.split('?').next().unwrap_or_default();
+    path.split('/')
+        .enumerate()
+        .map(|(index, segment)| if index == 1 { "{id}" } else { segment })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// Метрики:
+//   yookassa_requests_total{endpoint, method, status, error_code} - счетчик
+//   yookassa_request_duration_seconds{endpoint, method} - гистограмма
+//   yookassa_request_retries_total{endpoint, method} - счетчик
+//   yookassa_circuit_breaker_transitions_total{from, to} - счетчик
+//   yookassa_webhooks_total{event} - счетчик
+#[cfg(feature = "metrics")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MetricsCrateSink;
+
+#[cfg(feature = "metrics")]
+impl MetricsSink for MetricsCrateSink {
+    fn request_completed(&self, request: &RequestMetrics<'_>) {
+        ::metrics::counter!(
+            "yookassa_requests_total",
+            "endpoint" => request.endpoint.to_string(),
+            "method" => request.method.to_string(),
+            "status" => request.outcome.label(),
+            "error_code" => request
+                .error_code
+                .map(|code| code.as_str().to_string())
+                .unwrap_or_default(),
+        )
+        .increment(1);
+        ::metrics::histogram!(
+            "yookassa_request_duration_seconds",
+            "endpoint" => request.endpoint.to_string(),
+            "method" => request.method.to_string(),
+        )
+        .record(request.latency.as_secs_f64());
+    }
+
+    fn request_retried(&self, endpoint: &str, method: &str, _attempt: u32) {
+        ::metrics::counter!(
+            "yookassa_request_retries_total",
+            "endpoint" => endpoint.to_string(),
+            "method" => method.to_string(),
+        )
+        .increment(1);
+    }
+
+    fn circuit_state_changed(&self, from: CircuitState, to: CircuitState) {
+        ::metrics::counter!(
+            "yookassa_circuit_breaker_transitions_total",
+            "from" => circuit_state_label(from),
+            "to" => circuit_state_label(to),
+        )
+        .increment(1);
+    }
+
+    fn webhook_received(&self, event: &NotificationEvent) {
+        ::metrics::counter!("yookassa_webhooks_total", "event" => event.as_str().to_string())
+            .increment(1);
+    }
+}
+
+#[cfg(feature = "metrics")]
+fn circuit_state_label(state: CircuitState) -> &'static str {
+    match state {
+        CircuitState::Closed => "closed",
+        CircuitState::Open => "open",
+        CircuitState::HalfOpen => "half_open",
+    }
+}
@@ -0,0 +1,865 @@
+// --- Локальный mock-сервер YooKassa для интеграционных тестов (feature "mock") ---
+// Поднимается на 127.0.0.1 на свободном порту и реализует платежи, подтверждение,
+// отмену, возвраты, списки и /me с теми же переходами статусов, что и настоящий API.
+// Платежи с CardData ведут себя как тестовые карты YooKassa (см. sandbox).
+//
+//     let server = MockServer::start().await?;
+//     let client = server.client()?;
+//     let payment = client.create_payment(&request).await?;
+//     server.confirm_payment(&payment.id); // Имитация оплаты пользователем
+
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+use url::Url;
+use uuid::Uuid;
+
+use crate::sandbox::{self, CardScenario};
+use crate::{
+    Amount, ApiErrorCode, CancellationDetails, CancellationParty, CancellationReason,
+    CapturePaymentRequest, CardData, CardDetails, ConfirmationResponse, ConfirmationType,
+    CreatePaymentRequest, CreateRefundRequest, Me, Payment, PaymentList, PaymentMethod,
+    PaymentMethodType, PaymentStateError, PaymentStatus, Recipient, Refund, RefundList,
+    RefundStatus, ShopStatus, Timestamp, YooKassaApiError, YooKassaClient, YooKassaError,
+};
+
+pub const MOCK_ACCOUNT_ID: &str = "100500";
+const MOCK_GATEWAY_ID: &str = "100700";
+const MAX_REQUEST_BYTES: usize = 1024 * 1024;
+const CAPTURE_WINDOW: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+// Сбой, который сервер вернет на следующий запрос вместо обычного ответа
+#[derive(Debug, Clone)]
+pub enum MockFailure {
+    // Ответ с ошибкой в формате YooKassaApiError
+    ApiError {
+        status: u16,
+        code: ApiErrorCode,
+        description: String,
+    },
+    // Закрыть соединение, не отправив ответ
+    Disconnect,
+    // Ответить как обычно, но с задержкой
+    Delay(Duration),
+}
+
+impl MockFailure {
+    pub fn too_many_requests() -> Self {
+        MockFailure::ApiError {
+            status: 429,
+            code: ApiErrorCode::TooManyRequests,
+            description: "Too many requests".to_string(),
+        }
+    }
+
+    pub fn internal_server_error() -> Self {
+        MockFailure::ApiError {
+            status: 500,
+            code: ApiErrorCode::InternalServerError,
+            description: "Internal server error".to_string(),
+        }
+    }
+}
+
+// Запрос, полученный сервером (для проверок в тестах)
+#[derive(Debug, Clone)]
+pub struct MockRequestRecord {
+    pub method: String,
+    pub path: String,
+    pub idempotence_key: Option<String>,
+    pub body: String,
+}
+
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<MockState>>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl MockServer {
+    pub async fn start() -> std::io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(MockState::new(format!("http://{addr}/"))));
+        let (shutdown_tx, mut shutdown_rx) = oneshot::channel();
+
+        let server_state = state.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = &mut shutdown_rx => break,
+                    accepted = listener.accept() => {
+                        if let Ok((stream, _)) = accepted {
+                            tokio::spawn(handle_connection(stream, server_state.clone()));
+                        }
+                    }
+                }
+            }
+        });
+
+        Ok(MockServer {
+            addr,
+            state,
+            shutdown: Some(shutdown_tx),
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    // Значение для YooKassaClient::set_base_url
+    pub fn base_url(&self) -> String {
+        format!("http://{}/v3/", self.addr)
+    }
+
+    pub fn client(&self) -> Result<YooKassaClient, YooKassaError> {
+        Ok(YooKassaClient::new(
+            MOCK_ACCOUNT_ID.to_string(),
+            "test_mock_secret_key".to_string(),
+        )?
+        .set_base_url(self.base_url()))
+    }
+
+    // Следующие запросы получат сбои в порядке добавления
+    pub fn fail_next(&self, failure: MockFailure) {
+        self.lock().failures.push_back(failure);
+    }
+
+    // Пользователь подтвердил платеж: pending -> waiting_for_capture или succeeded (если capture = true)
+    pub fn confirm_payment(&self, payment_id: &str) -> bool {
+        let mut state = self.lock();
+        let auto_capture = state.auto_capture.get(payment_id).copied().unwrap_or(false);
+        let scenario = state.card_scenarios.get(payment_id).cloned();
+        match state.payment_mut(payment_id) {
+            Some(payment) if payment.status == PaymentStatus::Pending => {
+                match scenario {
+                    Some(CardScenario::Decline(reason)) => decline(payment, reason),
+                    _ => authorize(payment, auto_capture),
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Отмена платежа со стороны YooKassa или банка, например по истечении срока
+    pub fn cancel_payment_with(
+        &self,
+        payment_id: &str,
+        party: CancellationParty,
+        reason: CancellationReason,
+    ) -> bool {
+        match self.lock().payment_mut(payment_id) {
+            Some(payment) if !payment.status.is_final() => {
+                cancel(payment, party, reason);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn payment(&self, payment_id: &str) -> Option<Payment> {
+        self.lock().payment_mut(payment_id).map(|p| p.clone())
+    }
+
+    pub fn requests(&self) -> Vec<MockRequestRecord> {
+        self.lock().requests.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MockState> {
+        lock_state(&self.state)
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+// --- HTTP ---
+
+struct MockRequest {
+    method: String,
+    path: Vec<String>, // Сегменты пути после /v3/
+    query: HashMap<String, String>,
+    headers: HashMap<String, String>, // Имена в нижнем регистре
+    body: Vec<u8>,
+}
+
+struct MockResponse {
+    status: u16,
+    body: String,
+}
+
+impl MockResponse {
+    fn json<T: Serialize>(value: &T) -> Self {
+        MockResponse {
+            status: 200,
+            body: serde_json::to_string(value).unwrap_or_default(),
+        }
+    }
+
+    fn error(status: u16, code: ApiErrorCode, description: &str, parameter: Option<&str>) -> Self {
+        let error = YooKassaApiError {
+            error_type: "error".to_string(),
+            id: Uuid::new_v4().to_string(),
+            code,
+            description: description.to_string(),
+            parameter: parameter.map(str::to_string),
+            extra: serde_json::Map::new(),
+        };
+        MockResponse {
+            status,
+            body: serde_json::to_string(&error).unwrap_or_default(),
+        }
+    }
+
+    fn invalid_request(description: &str, parameter: Option<&str>) -> Self {
+        Self::error(400, ApiErrorCode::InvalidRequest, description, parameter)
+    }
+
+    // Ошибка локальной проверки операции над платежом в формате ответа API
+    fn state_error(error: PaymentStateError) -> Self {
+        let parameter = match error {
+            PaymentStateError::InvalidStatus { .. }
+            | PaymentStateError::InvalidTransition { .. }
+            | PaymentStateError::NotRefundable => None,
+            PaymentStateError::InvalidAmount(_)
+            | PaymentStateError::CurrencyMismatch { .. }
+            | PaymentStateError::AmountExceedsAvailable { .. } => Some("amount"),
+        };
+        Self::invalid_request(&error.to_string(), parameter)
+    }
+
+    fn not_found() -> Self {
+        Self::error(404, ApiErrorCode::NotFound, "Object not found", None)
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, state: Arc<Mutex<MockState>>) {
+    let Some(request) = read_request(&mut stream).await else {
+        return;
+    };
+
+    let failure = {
+        let mut state = lock_state(&state);
+        state.record(&request);
+        state.failures.pop_front()
+    };
+    let response = match failure {
+        Some(MockFailure::Disconnect) => return,
+        Some(MockFailure::ApiError {
+            status,
+            code,
+            description,
+        }) => MockResponse::error(status, code, &description, None),
+        Some(MockFailure::Delay(delay)) => {
+            tokio::time::sleep(delay).await;
+            lock_state(&state).handle(&request)
+        }
+        None => lock_state(&state).handle(&request),
+    };
+
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason_phrase(response.status),
+        response.body.len()
+    );
+    let _ = stream.write_all(head.as_bytes()).await;
+    let _ = stream.write_all(response.body.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+async fn read_request(stream: &mut TcpStream) -> Option<MockRequest> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        if let Some(position) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break position;
+        }
+        if buffer.len() > MAX_REQUEST_BYTES {
+            return None;
+        }
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split(' ');
+    let method = request_line.next()?.to_string();
+    let target = request_line.next()?;
+
+    let headers: HashMap<String, String> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+
+    let content_length: usize = headers
+        .get("content-length")
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(0);
+    if content_length > MAX_REQUEST_BYTES {
+        return None;
+    }
+    let mut body = buffer[header_end + 4..].to_vec();
+    while body.len() < content_length {
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+    body.truncate(content_length);
+
+    let url = Url::parse(&format!("http://localhost{target}")).ok()?;
+    let path = url
+        .path_segments()?
+        .filter(|segment| !segment.is_empty())
+        .skip_while(|segment| *segment != "v3")
+        .skip(1)
+        .map(str::to_string)
+        .collect();
+    let query = url.query_pairs().into_owned().collect();
+
+    Some(MockRequest {
+        method,
+        path,
+        query,
+        headers,
+        body,
+    })
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        _ => "Unknown",
+    }
+}
+
+fn lock_state(state: &Mutex<MockState>) -> std::sync::MutexGuard<'_, MockState> {
+    state
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+// --- Состояние и обработчики ---
+
+struct StoredResponse {
+    request_fingerprint: String,
+    status: u16,
+    body: String,
+}
+
+struct MockState {
+    base_url: String,
+    payments: Vec<Payment>, // В порядке создания
+    refunds: Vec<Refund>,
+    auto_capture: HashMap<String, bool>,
+    card_scenarios: HashMap<String, CardScenario>, // Исход для платежей тестовыми картами
+    idempotency: HashMap<String, StoredResponse>,
+    failures: VecDeque<MockFailure>,
+    requests: Vec<MockRequestRecord>,
+}
+
+impl MockState {
+    fn new(base_url: String) -> Self {
+        MockState {
+            base_url,
+            payments: Vec::new(),
+            refunds: Vec::new(),
+            auto_capture: HashMap::new(),
+            card_scenarios: HashMap::new(),
+            idempotency: HashMap::new(),
+            failures: VecDeque::new(),
+            requests: Vec::new(),
+        }
+    }
+
+    fn payment_mut(&mut self, payment_id: &str) -> Option<&mut Payment> {
+        self.payments.iter_mut().find(|p| p.id == payment_id)
+    }
+
+    fn record(&mut self, request: &MockRequest) {
+        self.requests.push(MockRequestRecord {
+            method: request.method.clone(),
+            path: format!("/v3/{}", request.path.join("/")),
+            idempotence_key: request.headers.get("idempotence-key").cloned(),
+            body: String::from_utf8_lossy(&request.body).to_string(),
+        });
+    }
+
+    fn handle(&mut self, request: &MockRequest) -> MockResponse {
+        let idempotence_key = request.headers.get("idempotence-key").cloned();
+
+        if !request.headers.contains_key("authorization") {
+            return MockResponse::error(
+                401,
+                ApiErrorCode::InvalidCredentials,
+                "Authentication by given credentials failed",
+                None,
+            );
+        }
+
+        if request.method != "POST" {
+            return self.route(request);
+        }
+
+        // POST без ключа идемпотентности YooKassa отклоняет; повтор с тем же ключом
+        // и тем же телом возвращает сохраненный ответ, с другим телом - ошибку
+        let Some(key) = idempotence_key else {
+            return MockResponse::invalid_request(
+                "Idempotence key is required",
+                Some("Idempotence-Key"),
+            );
+        };
+        let fingerprint = format!(
+            "{}|{}",
+            request.path.join("/"),
+            String::from_utf8_lossy(&request.body)
+        );
+        if let Some(stored) = self.idempotency.get(&key) {
+            return if stored.request_fingerprint == fingerprint {
+                MockResponse {
+                    status: stored.status,
+                    body: stored.body.clone(),
+                }
+            } else {
+                MockResponse::invalid_request(
+                    "Idempotence key duplicated with another request",
+                    Some("Idempotence-Key"),
+                )
+            };
+        }
+        let response = self.route(request);
+        if response.status < 500 {
+            self.idempotency.insert(
+                key,
+                StoredResponse {
+                    request_fingerprint: fingerprint,
+                    status: response.status,
+                    body: response.body.clone(),
+                },
+            );
+        }
+        response
+    }
+
+    fn route(&mut self, request: &MockRequest) -> MockResponse {
+        let path: Vec<&str> = request.path.iter().map(String::as_str).collect();
+        match (request.method.as_str(), path.as_slice()) {
+            ("POST", ["payments"]) => with_body(request, |body| self.create_payment(body)),
+            ("GET", ["payments"]) => self.list_payments(&request.query),
+            ("GET", ["payments", id]) => self.get_payment(id),
+            ("POST", ["payments", id, "capture"]) => {
+                with_body(request, |body| self.capture_payment(id, body))
+            }
+            ("POST", ["payments", id, "cancel"]) => self.cancel_payment(id),
+            ("POST", ["refunds"]) => with_body(request, |body| self.create_refund(body)),
+            ("GET", ["refunds"]) => self.list_refunds(&request.query),
+            ("GET", ["refunds", id]) => self.get_refund(id),
+            ("GET", ["me"]) => MockResponse::json(&self.me()),
+            _ => MockResponse::error(
+                405,
+                ApiErrorCode::Unknown("method_not_allowed".to_string()),
+                "Method not allowed",
+                None,
+            ),
+        }
+    }
+
+    fn create_payment(&mut self, request: CreatePaymentRequest) -> MockResponse {
+        if !is_positive_amount(&request.amount) {
+            return MockResponse::invalid_request("Invalid amount", Some("amount.value"));
+        }
+        if let Some(receipt) = &request.receipt
+            && let Err(error) = receipt.validate_against(&request.amount)
+        {
+            return MockResponse::invalid_request(&error.to_string(), Some("receipt"));
+        }
+
+        let id = Uuid::new_v4().to_string();
+        let auto_capture = request.capture.unwrap_or(false);
+        let confirmation = request.confirmation.as_ref().map(|confirmation| {
+            let confirmation_url = (confirmation.confirmation_type == ConfirmationType::Redirect)
+                .then(|| format!("{}checkout/{id}", self.base_url));
+            ConfirmationResponse {
+                confirmation_type: confirmation.confirmation_type.clone(),
+                confirmation_url,
+                return_url: Some(confirmation.return_url.clone()),
+                enforce: confirmation.enforce,
+                locale: confirmation.locale.clone(),
+                confirmation_data: None,
+                extra: serde_json::Map::new(),
+            }
+        });
+        let payment_method = request
+            .payment_method_data
+            .as_ref()
+            .map(|data| {
+                let mut method = payment_method(data.payment_method_type.clone(), &request);
+                method.card = data.card.as_ref().map(card_details);
+                method
+            })
+            .or_else(|| {
+                request
+                    .payment_method_id
+                    .as_ref()
+                    .map(|_| payment_method(PaymentMethodType::BankCard, &request))
+            });
+
+        let mut payment = Payment {
+            id: id.clone(),
+            status: PaymentStatus::Pending,
+            amount: request.amount.clone(),
+            income_amount: None,
+            description: request.description.clone(),
+            recipient: Recipient {
+                account_id: MOCK_ACCOUNT_ID.to_string(),
+                gateway_id: MOCK_GATEWAY_ID.to_string(),
+                extra: serde_json::Map::new(),
+            },
+            payment_method,
+            captured_at: None,
+            created_at: timestamp(SystemTime::now()),
+            expires_at: None,
+            confirmation,
+            test: true,
+            paid: false,
+            refundable: false,
+            refunded_amount: None,
+            receipt_registration: None,
+            metadata: request.metadata.clone(),
+            cancellation_details: None,
+            authorization_details: None,
+            extra: serde_json::Map::new(),
+        };
+
+        // Без сценария подтверждения исход известен сразу: карта решает по таблице
+        // тестовых карт, сохраненный способ или токен проходят. Со сценарием платеж
+        // ждет confirm_payment.
+        let card_scenario = request
+            .payment_method_data
+            .as_ref()
+            .and_then(|data| data.card.as_ref())
+            .map(sandbox::card_scenario);
+        if request.confirmation.is_none() {
+            match &card_scenario {
+                Some(CardScenario::Success) => authorize(&mut payment, auto_capture),
+                Some(CardScenario::ThreeDSecure) => {
+                    decline(&mut payment, CancellationReason::ThreeDSecureFailed)
+                }
+                Some(CardScenario::Decline(reason)) => decline(&mut payment, reason.clone()),
+                None if request.payment_method_id.is_some() || request.payment_token.is_some() => {
+                    authorize(&mut payment, auto_capture)
+                }
+                None => {}
+            }
+        }
+
+        if let Some(scenario) = card_scenario {
+            self.card_scenarios.insert(id.clone(), scenario);
+        }
+        self.auto_capture.insert(id, auto_capture);
+        self.payments.push(payment.clone());
+        MockResponse::json(&payment)
+    }
+
+    fn get_payment(&mut self, payment_id: &str) -> MockResponse {
+        match self.payment_mut(payment_id) {
+            Some(payment) => MockResponse::json(payment),
+            None => MockResponse::not_found(),
+        }
+    }
+
+    fn capture_payment(
+        &mut self,
+        payment_id: &str,
+        request: CapturePaymentRequest,
+    ) -> MockResponse {
+        let Some(payment) = self.payment_mut(payment_id) else {
+            return MockResponse::not_found();
+        };
+        if let Err(error) = payment.check_capture(request.amount.as_ref()) {
+            return MockResponse::state_error(error);
+        }
+        if let Some(amount) = request.amount {
+            payment.amount = amount;
+        }
+        succeed(payment);
+        MockResponse::json(payment)
+    }
+
+    fn cancel_payment(&mut self, payment_id: &str) -> MockResponse {
+        let Some(payment) = self.payment_mut(payment_id) else {
+            return MockResponse::not_found();
+        };
+        if let Err(error) = payment.check_cancel() {
+            return MockResponse::state_error(error);
+        }
+        cancel(
+            payment,
+            CancellationParty::Merchant,
+            CancellationReason::CanceledByMerchant,
+        );
+        MockResponse::json(payment)
+    }
+
+    fn list_payments(&self, query: &HashMap<String, String>) -> MockResponse {
+        let status = query.get("status").map(|s| PaymentStatus::from(s.as_str()));
+        let payments: Vec<&Payment> = self
+            .payments
+            .iter()
+            .rev()
+            .filter(|p| status.as_ref().is_none_or(|status| p.status == *status))
+            .collect();
+        let (items, next_cursor) = match paginate(&payments, query) {
+            Ok(page) => page,
+            Err(response) => return response,
+        };
+        MockResponse::json(&PaymentList {
+            list_type: "list".to_string(),
+            items: items.into_iter().cloned().collect(),
+            next_cursor,
+            extra: serde_json::Map::new(),
+        })
+    }
+
+    fn create_refund(&mut self, request: CreateRefundRequest) -> MockResponse {
+        let Some(payment) = self.payment_mut(&request.payment_id) else {
+            return MockResponse::invalid_request("Payment not found", Some("payment_id"));
+        };
+        if let Err(error) = payment.check_refund(&request.amount) {
+            return MockResponse::state_error(error);
+        }
+        let refunded = payment
+            .refunded_amount
+            .as_ref()
+            .and_then(Amount::to_minor_units)
+            .unwrap_or(0)
+            + request.amount.to_minor_units().unwrap_or(0);
+        payment.refunded_amount = Some(Amount::from_minor_units(
+            refunded,
+            payment.amount.currency.clone(),
+        ));
+
+        let refund = Refund {
+            id: Uuid::new_v4().to_string(),
+            payment_id: request.payment_id,
+            status: RefundStatus::Succeeded,
+            amount: request.amount,
+            created_at: timestamp(SystemTime::now()),
+            description: request.description,
+            receipt_registration: None,
+            extra: serde_json::Map::new(),
+        };
+        self.refunds.push(refund.clone());
+        MockResponse::json(&refund)
+    }
+
+    fn get_refund(&self, refund_id: &str) -> MockResponse {
+        match self.refunds.iter().find(|r| r.id == refund_id) {
+            Some(refund) => MockResponse::json(refund),
+            None => MockResponse::not_found(),
+        }
+    }
+
+    fn list_refunds(&self, query: &HashMap<String, String>) -> MockResponse {
+        let payment_id = query.get("payment_id");
+        let refunds: Vec<&Refund> = self
+            .refunds
+            .iter()
+            .rev()
+            .filter(|r| payment_id.is_none_or(|id| r.payment_id == *id))
+            .collect();
+        let (items, next_cursor) = match paginate(&refunds, query) {
+            Ok(page) => page,
+            Err(response) => return response,
+        };
+        MockResponse::json(&RefundList {
+            list_type: "list".to_string(),
+            items: items.into_iter().cloned().collect(),
+            next_cursor,
+        })
+    }
+
+    fn me(&self) -> Me {
+        Me {
+            account_id: MOCK_ACCOUNT_ID.to_string(),
+            status: ShopStatus::Enabled,
+            test: true,
+            fiscalization_enabled: Some(true),
+            fiscalization: None,
+            payment_methods: Some(vec![
+                PaymentMethodType::BankCard,
+                PaymentMethodType::YooMoney,
+                PaymentMethodType::Sbp,
+                PaymentMethodType::SberPay,
+            ]),
+            itn: None,
+            payout_methods: None,
+            name: None,
+            payout_balance: None,
+            extra: serde_json::Map::new(),
+        }
+    }
+}
+
+fn with_body<T: DeserializeOwned>(
+    request: &MockRequest,
+    handler: impl FnOnce(T) -> MockResponse,
+) -> MockResponse {
+    match serde_json::from_slice(&request.body) {
+        Ok(body) => handler(body),
+        Err(error) => {
+            MockResponse::invalid_request(&format!("Invalid request body: {error}"), None)
+        }
+    }
+}
+
+// Курсор - смещение в списке, отсортированном от новых к старым
+fn paginate<T: Copy>(
+    items: &[T],
+    query: &HashMap<String, String>,
+) -> Result<(Vec<T>, Option<String>), MockResponse> {
+    let limit = match query.get("limit").map(|l| l.parse::<usize>()) {
+        None => 10,
+        Some(Ok(limit)) if (1..=100).contains(&limit) => limit,
+        Some(_) => {
+            return Err(MockResponse::invalid_request(
+                "Invalid limit",
+                Some("limit"),
+            ));
+        }
+    };
+    let offset = match query.get("cursor").map(|c| c.parse::<usize>()) {
+        None => 0,
+        Some(Ok(offset)) => offset,
+        Some(Err(_)) => {
+            return Err(MockResponse::invalid_request(
+                "Invalid cursor",
+                Some("cursor"),
+            ));
+        }
+    };
+    let page: Vec<T> = items.iter().skip(offset).take(limit).copied().collect();
+    let next_cursor = (offset + limit < items.len()).then(|| (offset + limit).to_string());
+    Ok((page, next_cursor))
+}
+
+fn payment_method(
+    payment_method_type: PaymentMethodType,
+    request: &CreatePaymentRequest,
+) -> PaymentMethod {
+    PaymentMethod {
+        payment_method_type,
+        id: request
+            .payment_method_id
+            .clone()
+            .unwrap_or_else(|| Uuid::new_v4().to_string()),
+        saved: request.save_payment_method.unwrap_or(false) || request.payment_method_id.is_some(),
+        title: None,
+        card: None,
+        login: None,
+        phone: None,
+        sbp_operation_id: None,
+        payer_bank_details: None,
+        extra: serde_json::Map::new(),
+    }
+}
+
+// Пользователь прошел оплату: деньги заблокированы или сразу списаны
+fn authorize(payment: &mut Payment, auto_capture: bool) {
+    payment.paid = true;
+    if auto_capture {
+        succeed(payment);
+    } else {
+        payment.status = PaymentStatus::WaitingForCapture;
+        payment.expires_at = Some(timestamp(SystemTime::now() + CAPTURE_WINDOW));
+    }
+}
+
+fn succeed(payment: &mut Payment) {
+    payment.status = PaymentStatus::Succeeded;
+    payment.paid = true;
+    payment.refundable = true;
+    payment.captured_at = Some(timestamp(SystemTime::now()));
+    payment.expires_at = None;
+    payment.income_amount = Some(payment.amount.clone());
+}
+
+fn decline(payment: &mut Payment, reason: CancellationReason) {
+    let party = CardScenario::Decline(reason.clone())
+        .cancellation_party()
+        .unwrap_or(CancellationParty::PaymentNetwork);
+    cancel(payment, party, reason);
+}
+
+fn card_details(card: &CardData) -> CardDetails {
+    let number: String = card
+        .number
+        .expose_secret()
+        .chars()
+        .filter(char::is_ascii_digit)
+        .collect();
+    CardDetails {
+        first6: number.get(..6).map(str::to_string),
+        last4: number
+            .get(number.len().saturating_sub(4)..)
+            .unwrap_or_default()
+            .to_string(),
+        expiry_year: card.expiry_year.clone(),
+        expiry_month: card.expiry_month.clone(),
+        card_type: sandbox::card_type(&number).to_string(),
+        issuer_country: Some("RU".to_string()),
+        issuer_name: None,
+        source: None,
+        card_product: None,
+        extra: serde_json::Map::new(),
+    }
+}
+
+fn cancel(payment: &mut Payment, party: CancellationParty, reason: CancellationReason) {
+    payment.status = PaymentStatus::Canceled;
+    payment.paid = false;
+    payment.refundable = false;
+    payment.expires_at = None;
+    payment.cancellation_details = Some(CancellationDetails {
+        party,
+        reason,
+        extra: serde_json::Map::new(),
+    });
+}
+
+fn is_positive_amount(amount: &Amount) -> bool {
+    amount.to_minor_units().is_some_and(|value| value > 0) && !amount.currency.is_empty()
+}
+
+// Timestamp из SystemTime в формате ответа API ("2024-01-01T12:00:00.000Z")
+fn timestamp(time: SystemTime) -> Timestamp {
+    let time = chrono::DateTime::<chrono::Utc>::from(time);
+    Timestamp::new(time.to_rfc3339_opts(chrono::SecondsFormat::Millis, true))
+}
@@ -0,0 +1,356 @@
+// --- OAuth для партнеров YooKassa ---
+// Схема: authorize_url -> пользователь подтверждает доступ -> exchange_code -> OAuthToken,
+// который передается в YooKassaClient::with_oauth_token.
+
+use reqwest::header::{ACCEPT, HeaderValue};
+use reqwest::{Client as ReqwestClient, Response};
+use serde::{Deserialize, Deserializer, Serialize};
+use url::Url;
+
+use crate::transport::default_reqwest_client;
+use crate::{SecretString, YooKassaError};
+
+const YOOKASSA_OAUTH_BASE_URL: &str = "https://yookassa.ru/oauth/v2/";
+
+// OAuth-токен магазина (ответ на обмен кода авторизации)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OAuthToken {
+    pub access_token: SecretString,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_expires_in",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub expires_in: Option<u64>, // Время жизни токена в секундах
+}
+
+impl OAuthToken {
+    pub fn new(access_token: String) -> Self {
+        OAuthToken {
+            access_token: access_token.into(),
+            expires_in: None,
+        }
+    }
+}
+
+// Информация о токене
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TokenInfo {
+    pub status: String, // "valid", "revoked", "expired"
+    #[serde(default)]
+    pub scope: Vec<String>, // Права, выданные приложению ("payments", "refunds", etc.)
+    #[serde(
+        default,
+        deserialize_with = "deserialize_expires_in",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub expires_in: Option<u64>,
+}
+
+impl TokenInfo {
+    pub fn is_valid(&self) -> bool {
+        self.status == "valid"
+    }
+}
+
+// Тело ошибки OAuth-сервера
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OAuthErrorBody {
+    pub error: String, // "invalid_grant", "invalid_client", etc.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_description: Option<String>,
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("OAuth сервер YooKassa вернул статус {status}: {message}")]
+pub struct OAuthError {
+    pub status: u16,
+    pub message: String,
+    pub error_details: Option<OAuthErrorBody>,
+}
+
+// expires_in в ответах приходит то строкой, то числом
+fn deserialize_expires_in<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrNumber {
+        String(String),
+        Number(u64),
+    }
+
+    match Option::<StringOrNumber>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(StringOrNumber::Number(n)) => Ok(Some(n)),
+        Some(StringOrNumber::String(s)) => s.parse().map(Some).map_err(serde::de::Error::custom),
+    }
+}
+
+#[derive(Clone)]
+pub struct OAuthClient {
+    client: ReqwestClient,
+    client_id: String,
+    client_secret: SecretString,
+    base_url: String,
+}
+
+impl OAuthClient {
+    pub fn new(
+        client_id: String,
+        client_secret: impl Into<SecretString>,
+    ) -> Result<Self, YooKassaError> {
+        Ok(OAuthClient {
+            client: default_reqwest_client()?,
+            client_id,
+            client_secret: client_secret.into(),
+            base_url: YOOKASSA_OAUTH_BASE_URL.to_string(),
+        })
+    }
+
+    pub fn set_base_url(mut self, base_url: String) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    // URL, на который нужно отправить владельца магазина для выдачи доступа.
+    // state вернется в redirect_uri вместе с кодом - проверяйте его для защиты от CSRF.
+    pub fn authorize_url(&self, state: &str) -> Result<Url, YooKassaError> {
+        let mut url = Url::parse(&self.base_url)?.join("authorize")?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.client_id)
+            .append_pair("state", state);
+        Ok(url)
+    }
+
+    pub async fn exchange_code(&self, code: &str) -> Result<OAuthToken, YooKassaError> {
+        let response = self
+            .send_form(
+                "token",
+                &[("grant_type", "authorization_code"), ("code", code)],
+            )
+            .await?;
+        Ok(Self::process_response(response).await?.json().await?)
+    }
+
+    pub async fn token_info(&self, token: &OAuthToken) -> Result<TokenInfo, YooKassaError> {
+        let response = self
+            .send_form(
+                "token/info",
+                &[("token", token.access_token.expose_secret())],
+            )
+            .await?;
+        Ok(Self::process_response(response).await?.json().await?)
+    }
+
+    pub async fn revoke_token(&self, token: &OAuthToken) -> Result<(), YooKassaError> {
+        let response = self
+            .send_form(
+                "revoke_token",
+                &[("token", token.access_token.expose_secret())],
+            )
+            .await?;
+        Self::process_response(response).await?;
+        Ok(())
+    }
+
+    async fn send_form(
+        &self,
+        endpoint: &str,
+        form: &[(&str, &str)],
+    ) -> Result<Response, YooKassaError> {
+        let url = format!("{}{}", self.base_url, endpoint);
+        let response = self
+            .client
+            .post(url)
+            .basic_auth(&self.client_id, Some(self.client_secret.expose_secret()))
+            .header(ACCEPT, HeaderValue::from_static("application/json"))
+            .form(form)
+            .send()
+            .await?;
+        Ok(response)
+    }
+
+    async fn process_response(response: Response) -> Result<Response, YooKassaError> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let body_text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Не удалось прочитать тело ответа".to_string());
+        let error_details: Option<OAuthErrorBody> = serde_json::from_str(&body_text).ok();
+        let message = match &error_details {
+            Some(OAuthErrorBody {
+                error,
+                error_description: Some(description),
+            }) => format!("{error}: {description}"),
+            Some(OAuthErrorBody { error, .. }) => error.clone(),
+            None => body_text,
+        };
+        Err(OAuthError {
+            status: status.as_u16(),
+            message,
+            error_details,
+        }
+        .into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD as BASE64;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+
+    // Запрос, полученный тестовым сервером: строка запроса, заголовки (имена в нижнем
+    // регистре) и тело
+    struct CapturedRequest {
+        request_line: String,
+        headers: Vec<(String, String)>,
+        body: String,
+    }
+
+    impl CapturedRequest {
+        fn header(&self, name: &str) -> Option<&str> {
+            self.headers
+                .iter()
+                .find(|(header, _)| header == name)
+                .map(|(_, value)| value.as_str())
+        }
+    }
+
+    // Сервер на один запрос: отвечает status и body, возвращает base URL и полученный запрос
+    fn serve_once(status: u16, body: &'static str) -> (String, mpsc::Receiver<CapturedRequest>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}/oauth/v2/", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buffer = Vec::new();
+            let mut chunk = [0u8; 1024];
+            let header_end = loop {
+                if let Some(position) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+                    break position;
+                }
+                let read = stream.read(&mut chunk).unwrap();
+                buffer.extend_from_slice(&chunk[..read]);
+            };
+            let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+            let mut lines = head.split("\r\n");
+            let request_line = lines.next().unwrap().to_string();
+            let headers: Vec<(String, String)> = lines
+                .filter_map(|line| line.split_once(':'))
+                .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+                .collect();
+            let content_length: usize = headers
+                .iter()
+                .find(|(name, _)| name == "content-length")
+                .map_or(0, |(_, value)| value.parse().unwrap());
+            let mut request_body = buffer[header_end + 4..].to_vec();
+            while request_body.len() < content_length {
+                let read = stream.read(&mut chunk).unwrap();
+                request_body.extend_from_slice(&chunk[..read]);
+            }
+
+            let response = format!(
+                "HTTP/1.1 {status} OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+            sender
+                .send(CapturedRequest {
+                    request_line,
+                    headers,
+                    body: String::from_utf8(request_body).unwrap(),
+                })
+                .unwrap();
+        });
+        (base_url, receiver)
+    }
+
+    #[test]
+    fn authorize_url_encodes_parameters() {
+        let client = OAuthClient::new("app id".to_string(), "secret").unwrap();
+        let url = client.authorize_url("a b&c=d/é").unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://yookassa.ru/oauth/v2/authorize?response_type=code&client_id=app+id&state=a+b%26c%3Dd%2F%C3%A9"
+        );
+        let state = url
+            .query_pairs()
+            .find(|(name, _)| name == "state")
+            .map(|(_, value)| value.into_owned());
+        assert_eq!(state.as_deref(), Some("a b&c=d/é"));
+    }
+
+    #[tokio::test]
+    async fn exchange_code_request_shape() {
+        let (base_url, requests) = serve_once(
+            200,
+            r#"{"access_token":"AAEA.token","expires_in":"157680000"}"#,
+        );
+        let client = OAuthClient::new("client-id".to_string(), "client-secret")
+            .unwrap()
+            .set_base_url(base_url);
+
+        let token = client.exchange_code("code/with+chars").await.unwrap();
+        assert_eq!(token.access_token.expose_secret(), "AAEA.token");
+        assert_eq!(token.expires_in, Some(157_680_000));
+
+        let request = requests.recv().unwrap();
+        assert_eq!(request.request_line, "POST /oauth/v2/token HTTP/1.1");
+        let credentials = BASE64.encode("client-id:client-secret");
+        assert_eq!(
+            request.header("authorization"),
+            Some(format!("Basic {credentials}").as_str())
+        );
+        assert_eq!(request.header("accept"), Some("application/json"));
+        assert_eq!(
+            request.header("content-type"),
+            Some("application/x-www-form-urlencoded")
+        );
+        assert_eq!(
+            request.body,
+            "grant_type=authorization_code&code=code%2Fwith%2Bchars"
+        );
+    }
+
+    #[tokio::test]
+    async fn exchange_code_error_body() {
+        let (base_url, _requests) = serve_once(
+            400,
+            r#"{"error":"invalid_grant","error_description":"Code expired"}"#,
+        );
+        let client = OAuthClient::new("client-id".to_string(), "client-secret")
+            .unwrap()
+            .set_base_url(base_url);
+
+        let error = client.exchange_code("expired").await.unwrap_err();
+        let error = error.downcast_ref::<OAuthError>().unwrap();
+        assert_eq!(error.status, 400);
+        assert_eq!(error.message, "invalid_grant: Code expired");
+    }
+
+    #[tokio::test]
+    async fn revoke_token_sends_token() {
+        let (base_url, requests) = serve_once(200, "{}");
+        let client = OAuthClient::new("client-id".to_string(), "client-secret")
+            .unwrap()
+            .set_base_url(base_url);
+
+        client
+            .revoke_token(&OAuthToken::new("AAEA.token".to_string()))
+            .await
+            .unwrap();
+        let request = requests.recv().unwrap();
+        assert_eq!(request.request_line, "POST /oauth/v2/revoke_token HTTP/1.1");
+        assert_eq!(request.body, "token=AAEA.token");
+    }
+}
@@ -0,0 +1,413 @@
+// --- Допустимые операции и переходы статусов платежа ---
+// pending -> waiting_for_capture | succeeded | canceled
+// waiting_for_capture -> succeeded | canceled
+// succeeded и canceled - финальные статусы
+
+use crate::{Amount, Payment, PaymentStatus};
+
+// Операция над платежом, которую проверяют локальные guard'ы
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaymentOperation {
+    Capture,
+    Cancel,
+    Refund,
+}
+
+impl std::fmt::Display for PaymentOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            PaymentOperation::Capture => "capture",
+            PaymentOperation::Cancel => "cancel",
+            PaymentOperation::Refund => "refund",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PaymentStateError {
+    #[error("операция {operation} недоступна для платежа в статусе {status:?}")]
+    InvalidStatus {
+        operation: PaymentOperation,
+        status: PaymentStatus,
+    },
+    #[error("переход статуса {from:?} -> {to:?} недопустим")]
+    InvalidTransition {
+        from: PaymentStatus,
+        to: PaymentStatus,
+    },
+    #[error("платеж не поддерживает возврат (refundable = false)")]
+    NotRefundable,
+    #[error("некорректная сумма: {0}")]
+    InvalidAmount(String),
+    #[error("валюта {requested} не совпадает с валютой платежа {expected}")]
+    CurrencyMismatch { expected: String, requested: String },
+    #[error("сумма {requested} превышает доступную {available}")]
+    AmountExceedsAvailable {
+        requested: String,
+        available: String,
+    },
+}
+
+impl PaymentStatus {
+    // Статус больше не изменится
+    pub fn is_final(&self) -> bool {
+        matches!(self, PaymentStatus::Succeeded | PaymentStatus::Canceled)
+    }
+
+    pub fn can_transition_to(&self, next: &PaymentStatus) -> bool {
+        matches!(
+            (self, next),
+            (
+                PaymentStatus::Pending,
+                PaymentStatus::WaitingForCapture
+                    | PaymentStatus::Succeeded
+                    | PaymentStatus::Canceled
+            ) | (
+                PaymentStatus::WaitingForCapture,
+                PaymentStatus::Succeeded | PaymentStatus::Canceled
+            )
+        )
+    }
+
+    pub fn validate_transition(&self, next: &PaymentStatus) -> Result<(), PaymentStateError> {
+        if self.can_transition_to(next) {
+            Ok(())
+        } else {
+            Err(PaymentStateError::InvalidTransition {
+                from: self.clone(),
+                to: next.clone(),
+            })
+        }
+    }
+}
+
+impl Payment {
+    pub fn can_capture(&self) -> bool {
+        self.check_capture(None).is_ok()
+    }
+
+    pub fn can_cancel(&self) -> bool {
+        self.check_cancel().is_ok()
+    }
+
+    pub fn can_refund(&self, amount: &Amount) -> bool {
+        self.check_refund(amount).is_ok()
+    }
+
+    // Сумма, которую еще можно вернуть: amount - refunded_amount.
+    // None, если возврат по платежу невозможен.
+    pub fn remaining_refundable(&self) -> Option<Amount> {
+        if self.status != PaymentStatus::Succeeded || !self.refundable {
+            return None;
+        }
+        let total = self.amount.to_minor_units()?;
+        let refunded = match &self.refunded_amount {
+            Some(refunded) => refunded.to_minor_units()?,
+            None => 0,
+        };
+        Some(Amount::from_minor_units(
+            (total - refunded).max(0),
+            self.amount.currency.clone(),
+        ))
+    }
+
+    // Проверка перед capture_payment; amount - сумма частичного списания, если есть
+    pub fn check_capture(&self, amount: Option<&Amount>) -> Result<(), PaymentStateError> {
+        self.check_status(PaymentOperation::Capture)?;
+        match amount {
+            Some(amount) => check_amount_within(amount, &self.amount),
+            None => Ok(()),
+        }
+    }
+
+    // Проверка перед cancel_payment
+    pub fn check_cancel(&self) -> Result<(), PaymentStateError> {
+        self.check_status(PaymentOperation::Cancel)
+    }
+
+    // Проверка перед созданием возврата
+    pub fn check_refund(&self, amount: &Amount) -> Result<(), PaymentStateError> {
+        self.check_status(PaymentOperation::Refund)?;
+        if !self.refundable {
+            return Err(PaymentStateError::NotRefundable);
+        }
+        let available = self.remaining_refundable().ok_or_else(|| {
+            PaymentStateError::InvalidAmount(format!(
+                "не удалось вычислить остаток из amount {:?} и refunded_amount {:?}",
+                self.amount.value,
+                self.refunded_amount.as_ref().map(|a| &a.value)
+            ))
+        })?;
+        check_amount_within(amount, &available)
+    }
+
+    // Сколько осталось до автоматической отмены неподтвержденного платежа.
+    // Ok(None), если платеж не ждет подтверждения или expires_at нет; ноль, если срок уже
+    // истек. Err, если expires_at пришел в неразборчивом формате.
+    #[cfg(feature = "chrono")]
+    pub fn capture_deadline_remaining(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Option<chrono::TimeDelta>, chrono::ParseError> {
+        if self.status != PaymentStatus::WaitingForCapture {
+            return Ok(None);
+        }
+        let Some(expires_at) = &self.expires_at else {
+            return Ok(None);
+        };
+        let remaining = expires_at.to_chrono()? - now;
+        Ok(Some(remaining.max(chrono::TimeDelta::zero())))
+    }
+
+    #[cfg(feature = "chrono")]
+    pub fn is_capture_expired(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool, chrono::ParseError> {
+        Ok(self
+            .capture_deadline_remaining(now)?
+            .is_some_and(|remaining| remaining.is_zero()))
+    }
+
+    fn check_status(&self, operation: PaymentOperation) -> Result<(), PaymentStateError> {
+        let allowed = match operation {
+            PaymentOperation::Capture => self.status == PaymentStatus::WaitingForCapture,
+            PaymentOperation::Cancel => matches!(
+                self.status,
+                PaymentStatus::Pending | PaymentStatus::WaitingForCapture
+            ),
+            PaymentOperation::Refund => self.status == PaymentStatus::Succeeded,
+        };
+        if allowed {
+            Ok(())
+        } else {
+            Err(PaymentStateError::InvalidStatus {
+                operation,
+                status: self.status.clone(),
+            })
+        }
+    }
+}
+
+fn check_amount_within(requested: &Amount, available: &Amount) -> Result<(), PaymentStateError> {
+    if requested.currency != available.currency {
+        return Err(PaymentStateError::CurrencyMismatch {
+            expected: available.currency.clone(),
+            requested: requested.currency.clone(),
+        });
+    }
+    let requested_minor = requested
+        .to_minor_units()
+        .filter(|value| *value > 0)
+        .ok_or_else(|| PaymentStateError::InvalidAmount(requested.value.clone()))?;
+    let available_minor = available
+        .to_minor_units()
+        .ok_or_else(|| PaymentStateError::InvalidAmount(available.value.clone()))?;
+    if requested_minor > available_minor {
+        return Err(PaymentStateError::AmountExceedsAvailable {
+            requested: requested.value.clone(),
+            available: available.value.clone(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATUSES: [PaymentStatus; 4] = [
+        PaymentStatus::Pending,
+        PaymentStatus::WaitingForCapture,
+        PaymentStatus::Succeeded,
+        PaymentStatus::Canceled,
+    ];
+
+    fn payment(status: PaymentStatus, amount: &str, refunded: Option<&str>) -> Payment {
+        let mut payment: Payment = serde_json::from_value(serde_json::json!({
+            "id": "2d9a2c5d-000f-5000-9000-1b68e7b15f3f",
+            "status": "pending",
+            "amount": { "value": amount, "currency": "RUB" },
+            "recipient": { "account_id": "100500", "gateway_id": "100700" },
+            "created_at": "2024-01-01T12:00:00.000Z",
+            "test": true,
+            "paid": false,
+            "refundable": false,
+        }))
+        .unwrap();
+        payment.refundable = status == PaymentStatus::Succeeded;
+        payment.status = status;
+        payment.refunded_amount = refunded.map(rub);
+        payment
+    }
+
+    fn rub(value: &str) -> Amount {
+        Amount::new(value, "RUB")
+    }
+
+    #[test]
+    fn transitions() {
+        use PaymentStatus::*;
+        let allowed = [
+            (Pending, WaitingForCapture),
+            (Pending, Succeeded),
+            (Pending, Canceled),
+            (WaitingForCapture, Succeeded),
+            (WaitingForCapture, Canceled),
+        ];
+        for from in &STATUSES {
+            for to in &STATUSES {
+                let expected = allowed.contains(&(from.clone(), to.clone()));
+                assert_eq!(from.can_transition_to(to), expected, "{from:?} -> {to:?}");
+                assert_eq!(from.validate_transition(to).is_ok(), expected);
+            }
+        }
+        assert!(Succeeded.is_final() && Canceled.is_final());
+        assert!(!Pending.is_final() && !WaitingForCapture.is_final());
+        assert_eq!(
+            Succeeded.validate_transition(&Pending),
+            Err(PaymentStateError::InvalidTransition {
+                from: Succeeded,
+                to: Pending
+            })
+        );
+    }
+
+    #[test]
+    fn guards_by_status() {
+        for status in STATUSES {
+            let payment = payment(status.clone(), "100.00", None);
+            assert_eq!(
+                payment.can_capture(),
+                status == PaymentStatus::WaitingForCapture,
+                "{status:?}"
+            );
+            assert_eq!(
+                payment.can_cancel(),
+                matches!(
+                    status,
+                    PaymentStatus::Pending | PaymentStatus::WaitingForCapture
+                ),
+                "{status:?}"
+            );
+            assert_eq!(
+                payment.can_refund(&rub("1.00")),
+                status == PaymentStatus::Succeeded,
+                "{status:?}"
+            );
+        }
+        assert_eq!(
+            payment(PaymentStatus::Canceled, "100.00", None).check_cancel(),
+            Err(PaymentStateError::InvalidStatus {
+                operation: PaymentOperation::Cancel,
+                status: PaymentStatus::Canceled,
+            })
+        );
+    }
+
+    #[test]
+    fn partial_capture() {
+        let payment = payment(PaymentStatus::WaitingForCapture, "100.00", None);
+        assert_eq!(payment.check_capture(Some(&rub("60.00"))), Ok(()));
+        assert_eq!(payment.check_capture(Some(&rub("100.00"))), Ok(()));
+        assert_eq!(
+            payment.check_capture(Some(&rub("100.01"))),
+            Err(PaymentStateError::AmountExceedsAvailable {
+                requested: "100.01".to_string(),
+                available: "100.00".to_string(),
+            })
+        );
+        assert_eq!(
+            payment.check_capture(Some(&rub("0.00"))),
+            Err(PaymentStateError::InvalidAmount("0.00".to_string()))
+        );
+        assert_eq!(
+            payment.check_capture(Some(&rub("1,00"))),
+            Err(PaymentStateError::InvalidAmount("1,00".to_string()))
+        );
+        assert_eq!(
+            payment.check_capture(Some(&Amount::new("10.00", "USD"))),
+            Err(PaymentStateError::CurrencyMismatch {
+                expected: "RUB".to_string(),
+                requested: "USD".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn partial_refund() {
+        let partial = payment(PaymentStatus::Succeeded, "100.00", Some("30.50"));
+        assert_eq!(partial.remaining_refundable().unwrap().value, "69.50");
+        assert_eq!(partial.check_refund(&rub("69.50")), Ok(()));
+        assert_eq!(
+            partial.check_refund(&rub("69.51")),
+            Err(PaymentStateError::AmountExceedsAvailable {
+                requested: "69.51".to_string(),
+                available: "69.50".to_string(),
+            })
+        );
+
+        // Полностью возвращенный платеж
+        let refunded = payment(PaymentStatus::Succeeded, "100.00", Some("100.00"));
+        assert_eq!(refunded.remaining_refundable().unwrap().value, "0.00");
+        assert!(!refunded.can_refund(&rub("0.01")));
+    }
+
+    #[test]
+    fn refund_requires_refundable_payment() {
+        let mut not_refundable = payment(PaymentStatus::Succeeded, "100.00", None);
+        not_refundable.refundable = false;
+        assert!(not_refundable.remaining_refundable().is_none());
+        assert_eq!(
+            not_refundable.check_refund(&rub("1.00")),
+            Err(PaymentStateError::NotRefundable)
+        );
+
+        let pending = payment(PaymentStatus::Pending, "100.00", None);
+        assert!(pending.remaining_refundable().is_none());
+        assert_eq!(
+            pending.check_refund(&rub("1.00")),
+            Err(PaymentStateError::InvalidStatus {
+                operation: PaymentOperation::Refund,
+                status: PaymentStatus::Pending,
+            })
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn capture_deadline() {
+        use crate::Timestamp;
+        use chrono::{TimeDelta, TimeZone, Utc};
+
+        let now = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let mut waiting = payment(PaymentStatus::WaitingForCapture, "100.00", None);
+        waiting.expires_at = Some(Timestamp::new("2024-01-08T12:00:00.000Z"));
+        assert_eq!(
+            waiting.capture_deadline_remaining(now),
+            Ok(Some(TimeDelta::days(7)))
+        );
+        assert_eq!(waiting.is_capture_expired(now), Ok(false));
+
+        // Срок истек: остаток не уходит в минус
+        let later = now + TimeDelta::days(8);
+        assert_eq!(
+            waiting.capture_deadline_remaining(later),
+            Ok(Some(TimeDelta::zero()))
+        );
+        assert_eq!(waiting.is_capture_expired(later), Ok(true));
+
+        waiting.expires_at = Some(Timestamp::new("08.01.2024"));
+        assert!(waiting.capture_deadline_remaining(now).is_err());
+        assert!(waiting.is_capture_expired(now).is_err());
+
+        waiting.expires_at = None;
+        assert_eq!(waiting.capture_deadline_remaining(now), Ok(None));
+
+        // Для остальных статусов срока нет, даже если expires_at заполнен
+        let mut succeeded = payment(PaymentStatus::Succeeded, "100.00", None);
+        succeeded.expires_at = Some(Timestamp::new("2024-01-08T12:00:00.000Z"));
+        assert_eq!(succeeded.capture_deadline_remaining(now), Ok(None));
+        assert_eq!(succeeded.is_capture_expired(later), Ok(false));
+    }
+}
@@ -0,0 +1,344 @@
+// --- Клиенты для множества магазинов (платформы, маркетплейсы) ---
+// Все клиенты пула работают через один транспорт, то есть через общий пул соединений.
+// Учетные данные можно добавлять, заменять (ротация ключей) и удалять на лету;
+// клоны пула видят одни и те же магазины.
+//
+// Клиент магазина собирается через YooKassaClientBuilder в момент insert, поэтому
+// set_base_url, set_metrics и set_client_builder действуют только на магазины,
+// добавленные после вызова. Настраивайте пул до того, как добавлять магазины.
+//
+//     let pool = YooKassaClientPool::new()?.set_client_builder(|builder| {
+//         builder
+//             .retry_policy(RetryPolicy::new(3))
+//             .write_timeout(Duration::from_secs(60))
+//             .rate_limit(EndpointGroup::Payments, RateLimit::per_second(5.0))
+//     });
+//     pool.insert("100500".to_string(), "test_...")?;
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::builder::YooKassaClientBuilder;
+use crate::metrics_sink::MetricsSink;
+use crate::transport::{HttpTransport, default_reqwest_client};
+use crate::webhook::WebhookNotification;
+use crate::{
+    Credentials, OAuthToken, Payment, SecretString, YOOKASSA_API_BASE_URL, YooKassaClient,
+    YooKassaError,
+};
+
+type ConfigureBuilder = dyn Fn(YooKassaClientBuilder) -> YooKassaClientBuilder + Send + Sync;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PoolError {
+    #[error("магазин {0} не зарегистрирован в пуле")]
+    UnknownShop(String),
+    #[error("в объекте нет recipient.account_id, магазин не определить")]
+    NoRecipient,
+}
+
+#[derive(Clone)]
+pub struct YooKassaClientPool {
+    transport: Arc<dyn HttpTransport>,
+    base_url: String,
+    metrics: Option<Arc<dyn MetricsSink>>,
+    configure: Option<Arc<ConfigureBuilder>>,
+    clients: Arc<RwLock<HashMap<String, YooKassaClient>>>,
+}
+
+impl YooKassaClientPool {
+    pub fn new() -> Result<Self, YooKassaError> {
+        Ok(Self::with_transport(default_reqwest_client()?))
+    }
+
+    pub fn with_transport(transport: impl HttpTransport + 'static) -> Self {
+        YooKassaClientPool {
+            transport: Arc::new(transport),
+            base_url: YOOKASSA_API_BASE_URL.to_string(),
+            metrics: None,
+            configure: None,
+            clients: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    // Действует на магазины, добавленные после вызова
+    pub fn set_base_url(mut self, base_url: String) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    // Метрики запросов всех магазинов и уведомлений в parse_notification.
+    // Действует на магазины, добавленные после вызова
+    pub fn set_metrics(mut self, metrics: Arc<dyn MetricsSink>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    // Общие настройки клиентов магазинов: повторы, таймауты, лимиты частоты, circuit
+    // breaker, проверка чеков. Транспорт пула подставляется после configure, поэтому
+    // proxy и connect_timeout задаются на транспорте (with_transport), а не здесь.
+    // Лимиты частоты у каждого магазина свои; общий circuit breaker - если передать
+    // в configure один и тот же Arc. Действует на магазины, добавленные после вызова
+    pub fn set_client_builder(
+        mut self,
+        configure: impl Fn(YooKassaClientBuilder) -> YooKassaClientBuilder + Send + Sync + 'static,
+    ) -> Self {
+        self.configure = Some(Arc::new(configure));
+        self
+    }
+
+    // Добавляет магазин или заменяет его ключ. Возвращает true, если магазин уже был в пуле.
+    // Ошибка - если настройки из set_client_builder не собираются (см. YooKassaClientBuilder::build)
+    pub fn insert(
+        &self,
+        shop_id: String,
+        secret_key: impl Into<SecretString>,
+    ) -> Result<bool, YooKassaError> {
+        let credentials = Credentials::Basic {
+            shop_id: shop_id.clone(),
+            secret_key: secret_key.into(),
+        };
+        self.insert_credentials(shop_id, credentials)
+    }
+
+    // Магазин, подключенный через OAuth; shop_id - account_id магазина
+    pub fn insert_oauth(&self, shop_id: String, token: OAuthToken) -> Result<bool, YooKassaError> {
+        self.insert_credentials(shop_id, Credentials::OAuth(token))
+    }
+
+    fn insert_credentials(
+        &self,
+        shop_id: String,
+        credentials: Credentials,
+    ) -> Result<bool, YooKassaError> {
+        let mut builder =
+            YooKassaClientBuilder::with_credentials(credentials).base_url(self.base_url.clone());
+        if let Some(metrics) = &self.metrics {
+            builder = builder.metrics(metrics.clone());
+        }
+        if let Some(configure) = &self.configure {
+            builder = configure(builder);
+        }
+        let client = builder.transport(self.transport.clone()).build()?;
+        Ok(self.write().insert(shop_id, client).is_some())
+    }
+
+    // Возвращает true, если магазин был в пуле
+    pub fn remove(&self, shop_id: &str) -> bool {
+        self.write().remove(shop_id).is_some()
+    }
+
+    pub fn contains(&self, shop_id: &str) -> bool {
+        self.read().contains_key(shop_id)
+    }
+
+    pub fn shop_ids(&self) -> Vec<String> {
+        self.read().keys().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+
+    // Клиент магазина. Клон дешевый и продолжает работать со старым ключом после ротации,
+    // поэтому не храните его дольше одной операции.
+    pub fn get(&self, shop_id: &str) -> Result<YooKassaClient, PoolError> {
+        self.read()
+            .get(shop_id)
+            .cloned()
+            .ok_or_else(|| PoolError::UnknownShop(shop_id.to_string()))
+    }
+
+    // Клиент магазина, которому принадлежит платеж
+    pub fn client_for_payment(&self, payment: &Payment) -> Result<YooKassaClient, PoolError> {
+        self.get(&payment.recipient.account_id)
+    }
+
+    // Разбор входящего уведомления с учетом в метриках пула (webhook_received)
+    pub fn parse_notification(&self, body: &str) -> Result<WebhookNotification, YooKassaError> {
+        match &self.metrics {
+            Some(metrics) => WebhookNotification::from_json_with_metrics(body, metrics.as_ref()),
+            None => WebhookNotification::from_json(body),
+        }
+    }
+
+    // Клиент магазина, к которому пришло уведомление (по recipient.account_id объекта)
+    pub fn client_for_notification(
+        &self,
+        notification: &WebhookNotification,
+    ) -> Result<YooKassaClient, PoolError> {
+        let account_id = notification.account_id().ok_or(PoolError::NoRecipient)?;
+        self.get(account_id)
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, HashMap<String, YooKassaClient>> {
+        self.clients
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, HashMap<String, YooKassaClient>> {
+        self.clients
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::{HttpRequest, HttpResponse, TransportFuture};
+    use crate::{NotificationEvent, RetryPolicy};
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    const ME: &str = r#"{"account_id":"100500","status":"enabled","test":true}"#;
+
+    // Отвечает статусами из очереди (200, когда очередь пуста) и запоминает запросы
+    #[derive(Default)]
+    struct ScriptedTransport {
+        statuses: Mutex<VecDeque<u16>>,
+        requests: Mutex<Vec<(String, String)>>, // URL и Authorization
+    }
+
+    impl HttpTransport for ScriptedTransport {
+        fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+            let authorization = request
+                .authorization
+                .as_ref()
+                .map(|value| value.expose_secret().to_string())
+                .unwrap_or_default();
+            self.requests
+                .lock()
+                .unwrap()
+                .push((request.url.clone(), authorization));
+            let status = self.statuses.lock().unwrap().pop_front().unwrap_or(200);
+            Box::pin(std::future::ready(Ok(HttpResponse {
+                status,
+                body: ME.to_string(),
+            })))
+        }
+    }
+
+    #[derive(Default)]
+    struct WebhookCounter(AtomicUsize);
+
+    impl MetricsSink for WebhookCounter {
+        fn webhook_received(&self, _event: &NotificationEvent) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn notification(account_id: Option<&str>) -> String {
+        let mut object = serde_json::json!({ "id": "2d9a2c5d-000f-5000-9000-1b68e7b15f3f" });
+        if let Some(account_id) = account_id {
+            object["recipient"] = serde_json::json!({ "account_id": account_id });
+        }
+        serde_json::json!({
+            "type": "notification",
+            "event": "payment.succeeded",
+            "object": object,
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn insert_replace_remove() {
+        let pool = YooKassaClientPool::with_transport(ScriptedTransport::default());
+        assert!(pool.is_empty());
+        assert!(!pool.insert("100500".to_string(), "test_a").unwrap());
+        assert!(pool.insert("100500".to_string(), "test_b").unwrap());
+        assert!(!pool.insert("200600".to_string(), "test_c").unwrap());
+        assert_eq!(pool.len(), 2);
+        let mut shop_ids = pool.shop_ids();
+        shop_ids.sort();
+        assert_eq!(shop_ids, ["100500", "200600"]);
+
+        // Клоны пула видят одни и те же магазины
+        let clone = pool.clone();
+        assert!(clone.remove("100500"));
+        assert!(!pool.remove("100500"));
+        assert!(!pool.contains("100500"));
+        assert_eq!(
+            pool.get("100500").err(),
+            Some(PoolError::UnknownShop("100500".to_string()))
+        );
+    }
+
+    #[test]
+    fn notifications_are_routed_by_recipient() {
+        let metrics = Arc::new(WebhookCounter::default());
+        let pool = YooKassaClientPool::with_transport(ScriptedTransport::default())
+            .set_metrics(metrics.clone());
+        pool.insert("100500".to_string(), "test_a").unwrap();
+
+        let known = pool
+            .parse_notification(&notification(Some("100500")))
+            .unwrap();
+        assert!(pool.client_for_notification(&known).is_ok());
+
+        let unknown = pool
+            .parse_notification(&notification(Some("200600")))
+            .unwrap();
+        assert_eq!(
+            pool.client_for_notification(&unknown).err(),
+            Some(PoolError::UnknownShop("200600".to_string()))
+        );
+
+        let anonymous = pool.parse_notification(&notification(None)).unwrap();
+        assert_eq!(
+            pool.client_for_notification(&anonymous).err(),
+            Some(PoolError::NoRecipient)
+        );
+
+        // Метрики считаются при разборе, а не при выборе клиента
+        assert_eq!(metrics.0.load(Ordering::SeqCst), 3);
+        assert!(pool.parse_notification("{}").is_err());
+        assert_eq!(metrics.0.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn shops_share_transport_and_builder_settings() {
+        let transport = Arc::new(ScriptedTransport::default());
+        transport.statuses.lock().unwrap().push_back(503);
+        let pool = YooKassaClientPool::with_transport(transport.clone())
+            .set_base_url("https://proxy.example.com/v3/".to_string())
+            .set_client_builder(|builder| {
+                builder.retry_policy(
+                    RetryPolicy::new(1)
+                        .set_backoff(Duration::from_millis(1), Duration::from_millis(1)),
+                )
+            });
+        pool.insert("100500".to_string(), "test_a").unwrap();
+        pool.insert("200600".to_string(), "test_b").unwrap();
+
+        // Первый ответ 503 повторяется по политике из set_client_builder
+        pool.get("100500").unwrap().get_me().await.unwrap();
+        pool.get("200600").unwrap().get_me().await.unwrap();
+
+        let requests = transport.requests.lock().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert!(
+            requests
+                .iter()
+                .all(|(url, _)| url == "https://proxy.example.com/v3/me")
+        );
+        assert_eq!(requests[0].1, requests[1].1);
+        assert_ne!(requests[1].1, requests[2].1);
+    }
+
+    #[test]
+    fn invalid_builder_settings_reject_insert() {
+        // proxy относится к транспорту, а у пула транспорт общий
+        let pool = YooKassaClientPool::with_transport(ScriptedTransport::default())
+            .set_client_builder(|builder| builder.proxy("http://127.0.0.1:3128"));
+        assert!(pool.insert("100500".to_string(), "test_a").is_err());
+        assert!(pool.is_empty());
+    }
+}
@@ -0,0 +1,287 @@
+// --- Ограничение частоты и параллельности запросов на стороне клиента ---
+// Token bucket задает среднюю частоту и допустимый всплеск, семафор - число запросов
+// в полете. Лимиты задаются на группу эндпоинтов; клоны клиента делят один бюджет.
+//
+//     let client = YooKassaClient::builder(shop_id, secret_key)
+//         .rate_limit(EndpointGroup::Payments, RateLimit::per_second(5.0).max_in_flight(4))
+//         .build()?;
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+// Частота ниже одного запроса в час поднимается до него, чтобы ожидание токена
+// оставалось конечным (и Duration::from_secs_f64 не паниковал)
+const MIN_REQUESTS_PER_SECOND: f64 = 1.0 / 3600.0;
+
+// Группа эндпоинтов по первому сегменту пути
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EndpointGroup {
+    Payments,
+    Refunds,
+    Receipts,
+    Me,
+    Other,
+}
+
+impl EndpointGroup {
+    pub fn of(endpoint: &str) -> Self {
+        let segment = endpoint.split(['/', '?']).next().unwrap_or_default();
+        match segment {
+            "payments" => EndpointGroup::Payments,
+            "refunds" => EndpointGroup::Refunds,
+            "receipts" => EndpointGroup::Receipts,
+            "me" => EndpointGroup::Me,
+            _ => EndpointGroup::Other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    pub requests_per_second: Option<f64>, // None - без ограничения частоты
+    pub burst: u32,                       // Сколько запросов можно отправить подряд без ожидания
+    pub max_in_flight: Option<usize>,     // None - без ограничения параллельности
+}
+
+impl RateLimit {
+    // Ноль, отрицательное значение, NaN и бесконечность - без ограничения частоты;
+    // значения меньше одного запроса в час поднимаются до него
+    pub fn per_second(requests_per_second: f64) -> Self {
+        RateLimit {
+            requests_per_second: Some(requests_per_second),
+            burst: 1,
+            max_in_flight: None,
+        }
+    }
+
+    // Только ограничение параллельности
+    pub fn concurrency(max_in_flight: usize) -> Self {
+        RateLimit {
+            requests_per_second: None,
+            burst: 1,
+            max_in_flight: Some(max_in_flight),
+        }
+    }
+
+    pub fn burst(mut self, burst: u32) -> Self {
+        self.burst = burst.max(1);
+        self
+    }
+
+    pub fn max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = Some(max_in_flight);
+        self
+    }
+}
+
+struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    state: Mutex<(f64, Instant)>, // Токены и время последнего пополнения
+}
+
+impl TokenBucket {
+    fn new(rate: f64, burst: u32) -> Self {
+        let capacity = f64::from(burst.max(1));
+        TokenBucket {
+            rate,
+            capacity,
+            state: Mutex::new((capacity, Instant::now())),
+        }
+    }
+
+    // Забирает токен или возвращает, сколько ждать до его появления
+    fn try_take(&self) -> Result<(), Duration> {
+        let mut state = self
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let (tokens, last_refill) = &mut *state;
+        let now = Instant::now();
+        *tokens = (*tokens + now.duration_since(*last_refill).as_secs_f64() * self.rate)
+            .min(self.capacity);
+        *last_refill = now;
+        if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - *tokens) / self.rate))
+        }
+    }
+}
+
+struct GroupLimiter {
+    bucket: Option<TokenBucket>,
+    semaphore: Option<Semaphore>,
+}
+
+impl GroupLimiter {
+    fn new(limit: RateLimit) -> Self {
+        GroupLimiter {
+            bucket: limit
+                .requests_per_second
+                .filter(|rate| rate.is_finite() && *rate > 0.0)
+                .map(|rate| TokenBucket::new(rate.max(MIN_REQUESTS_PER_SECOND), limit.burst)),
+            semaphore: limit
+                .max_in_flight
+                .map(|max_in_flight| Semaphore::new(max_in_flight.max(1))),
+        }
+    }
+
+    async fn acquire(&self) -> Option<SemaphorePermit<'_>> {
+        // Сначала место в полете, потом токен: ожидающий слот запрос не тратит бюджет частоты
+        let permit = match &self.semaphore {
+            Some(semaphore) => semaphore.acquire().await.ok(),
+            None => None,
+        };
+        if let Some(bucket) = &self.bucket {
+            while let Err(wait) = bucket.try_take() {
+                tokio::time::sleep(wait).await;
+            }
+        }
+        permit
+    }
+}
+
+// Лимиты по группам; группа без своего лимита использует общий (если задан)
+#[derive(Default)]
+pub struct RateLimiter {
+    groups: HashMap<EndpointGroup, GroupLimiter>,
+    default: Option<GroupLimiter>,
+}
+
+// Разрешение на запрос; место в полете освобождается при удалении
+pub struct RatePermit<'a> {
+    _permit: Option<SemaphorePermit<'a>>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_limit(mut self, group: EndpointGroup, limit: RateLimit) -> Self {
+        self.groups.insert(group, GroupLimiter::new(limit));
+        self
+    }
+
+    pub fn set_default_limit(mut self, limit: RateLimit) -> Self {
+        self.default = Some(GroupLimiter::new(limit));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty() && self.default.is_none()
+    }
+
+    pub async fn acquire(&self, group: EndpointGroup) -> RatePermit<'_> {
+        let permit = match self.groups.get(&group).or(self.default.as_ref()) {
+            Some(limiter) => limiter.acquire().await,
+            None => None,
+        };
+        RatePermit { _permit: permit }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLOCKED: Duration = Duration::from_millis(50);
+
+    // Разрешение не выдается за BLOCKED - значит, лимит держит запрос
+    async fn is_blocked(limiter: &RateLimiter, group: EndpointGroup) -> bool {
+        tokio::time::timeout(BLOCKED, limiter.acquire(group))
+            .await
+            .is_err()
+    }
+
+    #[test]
+    fn token_bucket_wait() {
+        let bucket = TokenBucket::new(10.0, 1);
+        assert_eq!(bucket.try_take(), Ok(()));
+        let wait = bucket.try_take().unwrap_err();
+        assert!(wait <= Duration::from_millis(100), "{wait:?}");
+        assert!(wait > Duration::from_millis(80), "{wait:?}");
+
+        std::thread::sleep(Duration::from_millis(110));
+        assert_eq!(bucket.try_take(), Ok(()));
+    }
+
+    #[test]
+    fn token_bucket_burst() {
+        let bucket = TokenBucket::new(1.0, 3);
+        for _ in 0..3 {
+            assert_eq!(bucket.try_take(), Ok(()));
+        }
+        assert!(bucket.try_take().is_err());
+        assert_eq!(RateLimit::per_second(1.0).burst(0).burst, 1);
+    }
+
+    #[test]
+    fn extreme_rates_do_not_panic() {
+        for rate in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 0.0, -1.0] {
+            assert!(
+                GroupLimiter::new(RateLimit::per_second(rate))
+                    .bucket
+                    .is_none()
+            );
+        }
+
+        let limiter = GroupLimiter::new(RateLimit::per_second(f64::MIN_POSITIVE));
+        let bucket = limiter.bucket.unwrap();
+        assert_eq!(bucket.rate, MIN_REQUESTS_PER_SECOND);
+        assert_eq!(bucket.try_take(), Ok(()));
+        let wait = bucket.try_take().unwrap_err();
+        assert!(wait <= Duration::from_secs(3600), "{wait:?}");
+    }
+
+    #[tokio::test]
+    async fn max_in_flight_limits_concurrency() {
+        let limiter =
+            RateLimiter::new().set_limit(EndpointGroup::Payments, RateLimit::concurrency(2));
+        let first = limiter.acquire(EndpointGroup::Payments).await;
+        let second = limiter.acquire(EndpointGroup::Payments).await;
+        assert!(is_blocked(&limiter, EndpointGroup::Payments).await);
+
+        // Место освобождается при удалении разрешения
+        drop(first);
+        assert!(!is_blocked(&limiter, EndpointGroup::Payments).await);
+        drop(second);
+    }
+
+    #[tokio::test]
+    async fn groups_without_limit_use_default() {
+        let limiter = RateLimiter::new()
+            .set_limit(EndpointGroup::Payments, RateLimit::concurrency(1))
+            .set_default_limit(RateLimit::concurrency(1));
+
+        // Refunds и Me делят общий лимит, Payments - свой
+        let _refund = limiter.acquire(EndpointGroup::Refunds).await;
+        assert!(is_blocked(&limiter, EndpointGroup::Me).await);
+        assert!(!is_blocked(&limiter, EndpointGroup::Payments).await);
+
+        // Без общего лимита группа не ограничена
+        let limiter =
+            RateLimiter::new().set_limit(EndpointGroup::Payments, RateLimit::concurrency(1));
+        let _payment = limiter.acquire(EndpointGroup::Payments).await;
+        assert!(!is_blocked(&limiter, EndpointGroup::Refunds).await);
+        assert!(!is_blocked(&limiter, EndpointGroup::Refunds).await);
+    }
+
+    #[test]
+    fn endpoint_groups() {
+        assert_eq!(
+            EndpointGroup::of("payments/abc/capture"),
+            EndpointGroup::Payments
+        );
+        assert_eq!(
+            EndpointGroup::of("refunds?payment_id=1"),
+            EndpointGroup::Refunds
+        );
+        assert_eq!(EndpointGroup::of("me"), EndpointGroup::Me);
+        assert_eq!(EndpointGroup::of("payouts"), EndpointGroup::Other);
+    }
+}
@@ -0,0 +1,1102 @@
+// --- Справочники и проверки для чеков 54-ФЗ ---
+
+use crate::marking::{MARK_MODE_DEFAULT, Measure};
+use crate::{
+    Amount, CreateReceiptRequest, Payment, PaymentStatus, Receipt, ReceiptItem, ReceiptType,
+    Settlement, SettlementType,
+};
+
+api_int_enum! {
+    // Ставка НДС (vat_code)
+    pub enum VatCode {
+        NoVat => 1,            // Без НДС
+        Vat0 => 2,             // 0%
+        Vat10 => 3,            // 10%
+        Vat20 => 4,            // 20%
+        Vat10Calculated => 5,  // Расчетная ставка 10/110
+        Vat20Calculated => 6,  // Расчетная ставка 20/120
+        Vat5 => 7,             // 5%
+        Vat7 => 8,             // 7%
+        Vat5Calculated => 9,   // Расчетная ставка 5/105
+        Vat7Calculated => 10,  // Расчетная ставка 7/107
+        Vat22 => 11,           // 22%
+        Vat22Calculated => 12, // Расчетная ставка 22/122
+    }
+}
+
+api_int_enum! {
+    // Система налогообложения магазина (tax_system_code)
+    pub enum TaxSystemCode {
+        Osn => 1,               // Общая система налогообложения
+        UsnIncome => 2,         // Упрощенная (УСН, доходы)
+        UsnIncomeOutcome => 3,  // Упрощенная (УСН, доходы минус расходы)
+        Envd => 4,              // Единый налог на вмененный доход (ЕНВД)
+        Esn => 5,               // Единый сельскохозяйственный налог (ЕСН)
+        Patent => 6,            // Патентная система налогообложения
+    }
+}
+
+api_enum! {
+    // Признак способа расчета (payment_mode)
+    pub enum PaymentMode {
+        FullPrepayment => "full_prepayment",       // Полная предоплата
+        PartialPrepayment => "partial_prepayment", // Частичная предоплата
+        Advance => "advance",                      // Аванс
+        FullPayment => "full_payment",             // Полный расчет
+        PartialPayment => "partial_payment",       // Частичный расчет и кредит
+        Credit => "credit",                        // Кредит
+        CreditPayment => "credit_payment",         // Выплата по кредиту
+    }
+}
+
+api_enum! {
+    // Тип посредника, реализующего товар или услугу (agent_type)
+    pub enum AgentType {
+        BankingPaymentAgent => "banking_payment_agent",
+        BankingPaymentSubagent => "banking_payment_subagent",
+        PaymentAgent => "payment_agent",
+        PaymentSubagent => "payment_subagent",
+        Attorney => "attorney",
+        Commissioner => "commissioner",
+        Agent => "agent",
+    }
+}
+
+api_enum! {
+    // Признак предмета расчета (payment_subject)
+    pub enum PaymentSubject {
+        Commodity => "commodity",
+        Excise => "excise",
+        Job => "job",
+        Service => "service",
+        GamblingBet => "gambling_bet",
+        GamblingPrize => "gambling_prize",
+        Lottery => "lottery",
+        LotteryPrize => "lottery_prize",
+        IntellectualActivity => "intellectual_activity",
+        Payment => "payment",
+        AgentCommission => "agent_commission",
+        PropertyRight => "property_right",
+        NonOperatingGain => "non_operating_gain",
+        InsurancePremium => "insurance_premium",
+        SalesTax => "sales_tax",
+        ResortFee => "resort_fee",
+        Composite => "composite",
+        Another => "another",
+        // Значения ФФД 1.2
+        Fine => "fine",
+        Tax => "tax",
+        Lien => "lien",
+        Cost => "cost",
+        PensionInsuranceWithoutPayouts => "pension_insurance_without_payouts",
+        PensionInsuranceWithPayouts => "pension_insurance_with_payouts",
+        HealthInsuranceWithoutPayouts => "health_insurance_without_payouts",
+        HealthInsuranceWithPayouts => "health_insurance_with_payouts",
+        HealthInsurance => "health_insurance",
+        Casino => "casino",
+        AgentWithdrawals => "agent_withdrawals",
+        NonMarkedExcise => "non_marked_excise",
+        MarkedExcise => "marked_excise",
+        Marked => "marked",
+        NonMarked => "non_marked",
+    }
+}
+
+impl PaymentMode {
+    // Чек по предоплате, после которого нужен второй чек (полный расчет)
+    pub fn is_prepayment(&self) -> bool {
+        matches!(
+            self,
+            PaymentMode::FullPrepayment | PaymentMode::PartialPrepayment | PaymentMode::Advance
+        )
+    }
+}
+
+impl PaymentSubject {
+    // Товар, подлежащий маркировке
+    pub fn is_marked(&self) -> bool {
+        matches!(self, PaymentSubject::Marked | PaymentSubject::MarkedExcise)
+    }
+}
+
+// --- Проверка чека до отправки платежа ---
+
+const MAX_RECEIPT_ITEMS: usize = 100;
+const MAX_DESCRIPTION_CHARS: usize = 128;
+const MAX_PRODUCT_CODE_BYTES: usize = 32;
+
+// Одно нарушение в чеке; item - индекс позиции в receipt.items
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ReceiptViolation {
+    #[error("в чеке нет позиций")]
+    NoItems,
+    #[error("в чеке {0} позиций, допустимо не больше {MAX_RECEIPT_ITEMS}")]
+    TooManyItems(usize),
+    #[error("сумма позиций {items_total} не совпадает с суммой платежа {expected}")]
+    TotalMismatch {
+        expected: String,
+        items_total: String,
+    },
+    #[error("позиция {item}: пустое описание")]
+    EmptyDescription { item: usize },
+    #[error("позиция {item}: описание длиннее {MAX_DESCRIPTION_CHARS} символов ({length})")]
+    DescriptionTooLong { item: usize, length: usize },
+    #[error("позиция {item}: некорректное количество {value:?}")]
+    InvalidQuantity { item: usize, value: String },
+    #[error("позиция {item}: некорректная цена {value:?}")]
+    InvalidAmount { item: usize, value: String },
+    #[error("позиция {item}: валюта {currency} не совпадает с валютой платежа {expected}")]
+    CurrencyMismatch {
+        item: usize,
+        currency: String,
+        expected: String,
+    },
+    #[error("не указан ни email, ни телефон покупателя")]
+    MissingCustomerContact,
+    #[error("некорректный ИНН покупателя {0:?}")]
+    InvalidCustomerInn(String),
+    #[error("позиция {item}: маркированный товар без кода маркировки")]
+    MissingMarkCode { item: usize },
+    #[error("позиция {item}: некорректный product_code {value:?}")]
+    InvalidProductCode { item: usize, value: String },
+    #[error("позиция {item}: в mark_code_info должно быть заполнено ровно одно поле ({filled})")]
+    InvalidMarkCodeInfo { item: usize, filled: usize },
+    #[error("позиция {item}: mark_mode должен быть \"0\", а не {value:?}")]
+    InvalidMarkMode { item: usize, value: String },
+    #[error("позиция {item}: для mark_code_info нужен mark_mode")]
+    MissingMarkMode { item: usize },
+    #[error("позиция {item}: для маркированного товара нужна мера количества (measure)")]
+    MissingMeasure { item: usize },
+    #[error("позиция {item}: дробное количество допустимо только для штучного товара")]
+    MarkQuantityRequiresPiece { item: usize },
+    #[error("позиция {item}: для агентской схемы нужен ИНН поставщика (supplier.inn)")]
+    MissingSupplierInn { item: usize },
+    #[error("позиция {item}: некорректный ИНН поставщика {value:?}")]
+    InvalidSupplierInn { item: usize, value: String },
+    #[error("позиция {item}: некорректное дробное количество {numerator}/{denominator}")]
+    InvalidMarkQuantity {
+        item: usize,
+        numerator: i32,
+        denominator: i32,
+    },
+}
+
+// Все нарушения, найденные в чеке
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("чек не прошел проверку: {}", .violations.iter().map(|v| v.to_string()).collect::<Vec<_>>().join("; "))]
+pub struct ReceiptValidationError {
+    pub violations: Vec<ReceiptViolation>,
+}
+
+impl Receipt {
+    // Проверяет чек против суммы платежа так же, как это сделает YooKassa,
+    // и возвращает сразу все найденные нарушения.
+    pub fn validate_against(&self, amount: &Amount) -> Result<(), ReceiptValidationError> {
+        let mut violations = Vec::new();
+
+        if self.items.is_empty() {
+            violations.push(ReceiptViolation::NoItems);
+        }
+        if self.items.len() > MAX_RECEIPT_ITEMS {
+            violations.push(ReceiptViolation::TooManyItems(self.items.len()));
+        }
+
+        match &self.customer {
+            Some(customer) => {
+                let has_contact = [&customer.email, &customer.phone]
+                    .into_iter()
+                    .any(|contact| contact.as_deref().is_some_and(|c| !c.trim().is_empty()));
+                if !has_contact {
+                    violations.push(ReceiptViolation::MissingCustomerContact);
+                }
+                if let Some(inn) = &customer.inn
+                    && !is_inn_format(inn)
+                {
+                    violations.push(ReceiptViolation::InvalidCustomerInn(inn.clone()));
+                }
+            }
+            None => violations.push(ReceiptViolation::MissingCustomerContact),
+        }
+
+        let mut items_total = Some(0i64);
+        for (index, item) in self.items.iter().enumerate() {
+            let line_total = validate_item(index, item, amount, &mut violations);
+            items_total = match (items_total, line_total) {
+                (Some(total), Some(line)) => total.checked_add(line),
+                _ => None,
+            };
+        }
+
+        // Сумму сравниваем, только если все позиции удалось разобрать
+        if let (Some(items_total), Some(expected)) = (items_total, amount.to_minor_units())
+            && !self.items.is_empty()
+            && items_total != expected
+        {
+            violations.push(ReceiptViolation::TotalMismatch {
+                expected: amount.value.clone(),
+                items_total: Amount::from_minor_units(items_total, amount.currency.clone()).value,
+            });
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(ReceiptValidationError { violations })
+        }
+    }
+}
+
+impl ReceiptItem {
+    // Стоимость позиции (цена × количество) в копейках, округленная до копейки
+    pub fn total_minor_units(&self) -> Option<i64> {
+        let price = self.amount.to_minor_units()?;
+        let quantity = parse_quantity_thousandths(&self.quantity)?;
+        Some(round_thousandths(price.checked_mul(quantity)?))
+    }
+}
+
+// Возвращает стоимость позиции в копейках, если цену и количество удалось разобрать
+fn validate_item(
+    index: usize,
+    item: &ReceiptItem,
+    amount: &Amount,
+    violations: &mut Vec<ReceiptViolation>,
+) -> Option<i64> {
+    let description_length = item.description.chars().count();
+    if item.description.trim().is_empty() {
+        violations.push(ReceiptViolation::EmptyDescription { item: index });
+    } else if description_length > MAX_DESCRIPTION_CHARS {
+        violations.push(ReceiptViolation::DescriptionTooLong {
+            item: index,
+            length: description_length,
+        });
+    }
+
+    let quantity = parse_quantity_thousandths(&item.quantity).filter(|q| *q > 0);
+    if quantity.is_none() {
+        violations.push(ReceiptViolation::InvalidQuantity {
+            item: index,
+            value: item.quantity.clone(),
+        });
+    }
+
+    let price = item.amount.to_minor_units();
+    if price.is_none() {
+        violations.push(ReceiptViolation::InvalidAmount {
+            item: index,
+            value: item.amount.value.clone(),
+        });
+    }
+    if item.amount.currency != amount.currency {
+        violations.push(ReceiptViolation::CurrencyMismatch {
+            item: index,
+            currency: item.amount.currency.clone(),
+            expected: amount.currency.clone(),
+        });
+    }
+
+    validate_marking(index, item, violations);
+    validate_agent(index, item, violations);
+
+    match (price, quantity) {
+        (Some(_), Some(_)) => item.total_minor_units(),
+        _ => None,
+    }
+}
+
+fn validate_marking(index: usize, item: &ReceiptItem, violations: &mut Vec<ReceiptViolation>) {
+    let is_marked = item
+        .payment_subject
+        .as_ref()
+        .is_some_and(PaymentSubject::is_marked);
+    let has_mark_code =
+        item.product_code.is_some() || item.product_mark.is_some() || item.mark_code_info.is_some();
+    if is_marked && !has_mark_code {
+        violations.push(ReceiptViolation::MissingMarkCode { item: index });
+    }
+
+    if let Some(mark_code_info) = &item.mark_code_info {
+        let filled = mark_code_info.filled_fields();
+        if filled != 1 {
+            violations.push(ReceiptViolation::InvalidMarkCodeInfo {
+                item: index,
+                filled,
+            });
+        }
+        if item.mark_mode.is_none() {
+            violations.push(ReceiptViolation::MissingMarkMode { item: index });
+        }
+        if item.measure.is_none() {
+            violations.push(ReceiptViolation::MissingMeasure { item: index });
+        }
+    }
+    if let Some(mark_mode) = &item.mark_mode
+        && mark_mode != MARK_MODE_DEFAULT
+    {
+        violations.push(ReceiptViolation::InvalidMarkMode {
+            item: index,
+            value: mark_mode.clone(),
+        });
+    }
+
+    if let Some(product_code) = &item.product_code
+        && !is_product_code_format(product_code)
+    {
+        violations.push(ReceiptViolation::InvalidProductCode {
+            item: index,
+            value: product_code.clone(),
+        });
+    }
+
+    if let Some(mark_quantity) = &item.mark_quantity {
+        if item.measure.as_ref().is_some_and(|m| *m != Measure::Piece) {
+            violations.push(ReceiptViolation::MarkQuantityRequiresPiece { item: index });
+        }
+        let valid = mark_quantity.numerator >= 1
+            && mark_quantity.denominator >= 1
+            && mark_quantity.numerator < mark_quantity.denominator;
+        if !valid {
+            violations.push(ReceiptViolation::InvalidMarkQuantity {
+                item: index,
+                numerator: mark_quantity.numerator,
+                denominator: mark_quantity.denominator,
+            });
+        }
+    }
+}
+
+fn validate_agent(index: usize, item: &ReceiptItem, violations: &mut Vec<ReceiptViolation>) {
+    let supplier_inn = item.supplier.as_ref().and_then(|s| s.inn.as_ref());
+    if item.agent_type.is_some() && supplier_inn.is_none() {
+        violations.push(ReceiptViolation::MissingSupplierInn { item: index });
+    }
+    if let Some(inn) = supplier_inn
+        && !is_valid_inn(inn)
+    {
+        violations.push(ReceiptViolation::InvalidSupplierInn {
+            item: index,
+            value: inn.clone(),
+        });
+    }
+}
+
+// Количество в тысячных долях: "2" -> 2000, "0.125" -> 125. Не больше 3 знаков после точки.
+pub(crate) fn parse_quantity_thousandths(quantity: &str) -> Option<i64> {
+    let (integer, fraction) = match quantity.split_once('.') {
+        Some((integer, fraction)) if !fraction.is_empty() => (integer, fraction),
+        Some(_) => return None,
+        None => (quantity, ""),
+    };
+    if integer.is_empty()
+        || fraction.len() > 3
+        || !integer.bytes().all(|b| b.is_ascii_digit())
+        || !fraction.bytes().all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    let integer: i64 = integer.parse().ok()?;
+    let fraction: i64 = format!("{fraction:0<3}").parse().ok()?;
+    integer.checked_mul(1000)?.checked_add(fraction)
+}
+
+// ИНН: 10 цифр для юрлица, 12 для физлица или ИП
+pub(crate) fn is_inn_format(inn: &str) -> bool {
+    matches!(inn.len(), 10 | 12) && inn.bytes().all(|b| b.is_ascii_digit())
+}
+
+// Формат и контрольные цифры ИНН
+pub fn is_valid_inn(inn: &str) -> bool {
+    const COEFFICIENTS_10: [u32; 9] = [2, 4, 10, 3, 5, 9, 4, 6, 8];
+    const COEFFICIENTS_11: [u32; 10] = [7, 2, 4, 10, 3, 5, 9, 4, 6, 8];
+    const COEFFICIENTS_12: [u32; 11] = [3, 7, 2, 4, 10, 3, 5, 9, 4, 6, 8];
+
+    if !is_inn_format(inn) {
+        return false;
+    }
+    let digits: Vec<u32> = inn.bytes().map(|b| u32::from(b - b'0')).collect();
+    let check_digit = |coefficients: &[u32]| {
+        let sum: u32 = coefficients.iter().zip(&digits).map(|(c, d)| c * d).sum();
+        sum % 11 % 10
+    };
+    match digits.len() {
+        10 => check_digit(&COEFFICIENTS_10) == digits[9],
+        _ => {
+            check_digit(&COEFFICIENTS_11) == digits[10]
+                && check_digit(&COEFFICIENTS_12) == digits[11]
+        }
+    }
+}
+
+// product_code - байты в HEX через пробел: "00 00 00 01 00 21 FA 41 ..."
+fn is_product_code_format(product_code: &str) -> bool {
+    let bytes: Vec<&str> = product_code.split(' ').collect();
+    !bytes.is_empty()
+        && bytes.len() <= MAX_PRODUCT_CODE_BYTES
+        && bytes
+            .iter()
+            .all(|byte| byte.len() == 2 && byte.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+// --- Сборка чека со скидкой на весь заказ ---
+// Скидка распределяется по позициям пропорционально их стоимости с точностью до копейки.
+// Копейки, оставшиеся после округления, достаются позициям с наибольшим дробным остатком
+// (при равенстве - позиции, добавленной раньше). Если цена после скидки не делится на
+// количество без остатка, позиция разбивается на две с ценами, отличающимися на копейку.
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ReceiptBuildError {
+    #[error("позиция {item}: некорректная цена {value:?}")]
+    InvalidPrice { item: usize, value: String },
+    #[error("позиция {item}: некорректное количество {value:?}")]
+    InvalidQuantity { item: usize, value: String },
+    #[error("валюта {currency} не совпадает с валютой чека {expected}")]
+    CurrencyMismatch { currency: String, expected: String },
+    #[error("некорректная сумма скидки {0:?}")]
+    InvalidDiscount(String),
+    #[error("скидка {discount} больше стоимости заказа {total}")]
+    DiscountExceedsTotal { discount: String, total: String },
+    #[error("позиция {item}: стоимость не помещается в допустимый диапазон сумм")]
+    AmountOverflow { item: usize },
+    #[error("после разбиения по ценам в чеке {0} позиций, допустимо не больше {MAX_RECEIPT_ITEMS}")]
+    TooManyItems(usize),
+}
+
+#[derive(Debug, Clone)]
+pub struct ReceiptBuilder {
+    currency: String,
+    items: Vec<ReceiptItem>,
+    discount: Option<Amount>,
+    customer: Option<crate::ReceiptCustomer>,
+    tax_system_code: Option<TaxSystemCode>,
+}
+
+impl ReceiptBuilder {
+    pub fn new(currency: impl Into<String>) -> Self {
+        ReceiptBuilder {
+            currency: currency.into(),
+            items: Vec::new(),
+            discount: None,
+            customer: None,
+            tax_system_code: None,
+        }
+    }
+
+    // Позиция с ценой за единицу до скидки в item.amount; остальные поля переносятся в чек как есть
+    pub fn item(mut self, item: ReceiptItem) -> Self {
+        self.items.push(item);
+        self
+    }
+
+    // Скидка на весь заказ
+    pub fn discount(mut self, discount: Amount) -> Self {
+        self.discount = Some(discount);
+        self
+    }
+
+    pub fn customer(mut self, customer: crate::ReceiptCustomer) -> Self {
+        self.customer = Some(customer);
+        self
+    }
+
+    pub fn tax_system_code(mut self, tax_system_code: TaxSystemCode) -> Self {
+        self.tax_system_code = Some(tax_system_code);
+        self
+    }
+
+    pub fn build(self) -> Result<Receipt, ReceiptBuildError> {
+        let mut quantities = Vec::with_capacity(self.items.len());
+        let mut line_totals = Vec::with_capacity(self.items.len());
+        for (index, item) in self.items.iter().enumerate() {
+            if item.amount.currency != self.currency {
+                return Err(ReceiptBuildError::CurrencyMismatch {
+                    currency: item.amount.currency.clone(),
+                    expected: self.currency.clone(),
+                });
+            }
+            let price =
+                item.amount
+                    .to_minor_units()
+                    .ok_or_else(|| ReceiptBuildError::InvalidPrice {
+                        item: index,
+                        value: item.amount.value.clone(),
+                    })?;
+            let quantity = parse_quantity_thousandths(&item.quantity)
+                .filter(|q| *q > 0)
+                .ok_or_else(|| ReceiptBuildError::InvalidQuantity {
+                    item: index,
+                    value: item.quantity.clone(),
+                })?;
+            let line_total = price
+                .checked_mul(quantity)
+                .map(round_thousandths)
+                .ok_or(ReceiptBuildError::AmountOverflow { item: index })?;
+            quantities.push(quantity);
+            line_totals.push(line_total);
+        }
+
+        let discount = match &self.discount {
+            Some(discount) => {
+                if discount.currency != self.currency {
+                    return Err(ReceiptBuildError::CurrencyMismatch {
+                        currency: discount.currency.clone(),
+                        expected: self.currency.clone(),
+                    });
+                }
+                discount
+                    .to_minor_units()
+                    .ok_or_else(|| ReceiptBuildError::InvalidDiscount(discount.value.clone()))?
+            }
+            None => 0,
+        };
+        let mut total: i64 = 0;
+        for (index, line_total) in line_totals.iter().enumerate() {
+            total = total
+                .checked_add(*line_total)
+                .ok_or(ReceiptBuildError::AmountOverflow { item: index })?;
+        }
+        if discount > total {
+            return Err(ReceiptBuildError::DiscountExceedsTotal {
+                discount: Amount::from_minor_units(discount, self.currency.clone()).value,
+                total: Amount::from_minor_units(total, self.currency.clone()).value,
+            });
+        }
+
+        let shares = distribute_proportionally(discount, &line_totals);
+        let mut items = Vec::with_capacity(self.items.len());
+        for (index, ((item, quantity), (line_total, share))) in self
+            .items
+            .into_iter()
+            .zip(quantities)
+            .zip(line_totals.into_iter().zip(shares))
+            .enumerate()
+        {
+            let parts = split_line(line_total - share, quantity)
+                .ok_or(ReceiptBuildError::AmountOverflow { item: index })?;
+            for (part_quantity, unit_price) in parts {
+                let mut part = item.clone();
+                part.quantity = format_quantity_thousandths(part_quantity);
+                part.amount = Amount::from_minor_units(unit_price, self.currency.clone());
+                items.push(part);
+            }
+        }
+        // Разбиение добавляет по строке на позицию, поэтому лимит проверяется после него
+        if items.len() > MAX_RECEIPT_ITEMS {
+            return Err(ReceiptBuildError::TooManyItems(items.len()));
+        }
+
+        Ok(Receipt {
+            customer: self.customer,
+            items,
+            tax_system_code: self.tax_system_code,
+            receipt_industry_details: None,
+            receipt_operational_details: None,
+        })
+    }
+}
+
+// Делит amount пропорционально весам (метод наибольшего остатка)
+fn distribute_proportionally(amount: i64, weights: &[i64]) -> Vec<i64> {
+    let total: i64 = weights.iter().sum();
+    if total == 0 || amount == 0 {
+        return vec![0; weights.len()];
+    }
+    let mut shares = Vec::with_capacity(weights.len());
+    let mut remainders = Vec::with_capacity(weights.len());
+    for (index, weight) in weights.iter().enumerate() {
+        let exact = amount as i128 * *weight as i128;
+        shares.push((exact / total as i128) as i64);
+        remainders.push((exact % total as i128, index));
+    }
+    let mut left = amount - shares.iter().sum::<i64>();
+    // Больший остаток - раньше, при равных остатках - меньший индекс
+    remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    for (_, index) in remainders {
+        if left == 0 {
+            break;
+        }
+        shares[index] += 1;
+        left -= 1;
+    }
+    shares
+}
+
+// Разбивает стоимость позиции на строки (количество в тысячных, цена в копейках),
+// у которых цена × количество в сумме дает ровно line_total. None - при переполнении.
+fn split_line(line_total: i64, quantity: i64) -> Option<Vec<(i64, i64)>> {
+    if quantity % 1000 == 0 {
+        let units = quantity / 1000;
+        let price = line_total / units;
+        let extra = line_total % units;
+        return Some(if extra == 0 {
+            vec![(quantity, price)]
+        } else {
+            vec![((units - extra) * 1000, price), (extra * 1000, price + 1)]
+        });
+    }
+
+    let price = min_price_for_total(line_total, quantity)?;
+    if round_thousandths(price.checked_mul(quantity)?) == line_total {
+        return Some(vec![(quantity, price)]);
+    }
+
+    // Дробное количество больше единицы: целые единицы по одной цене, остаток - по своей
+    let whole = quantity / 1000 * 1000;
+    let fraction = quantity - whole;
+    let whole_price = line_total.checked_mul(1000)? / quantity;
+    let fraction_total = line_total - whole_price * (whole / 1000);
+    Some(vec![
+        (whole, whole_price),
+        (fraction, min_price_for_total(fraction_total, fraction)?),
+    ])
+}
+
+// Минимальная цена, при которой round(цена × количество) >= total
+fn min_price_for_total(total: i64, quantity: i64) -> Option<i64> {
+    let needed = total.checked_mul(1000)? - 500;
+    Some(if needed <= 0 {
+        0
+    } else {
+        needed / quantity + i64::from(needed % quantity != 0)
+    })
+}
+
+// Округление тысячных долей копейки до копейки (половина - вверх)
+fn round_thousandths(value: i64) -> i64 {
+    value / 1000 + i64::from(value % 1000 >= 500)
+}
+
+fn format_quantity_thousandths(quantity: i64) -> String {
+    let integer = quantity / 1000;
+    let fraction = quantity % 1000;
+    if fraction == 0 {
+        integer.to_string()
+    } else {
+        let fraction = format!("{fraction:03}");
+        format!("{integer}.{}", fraction.trim_end_matches('0'))
+    }
+}
+
+// --- Второй чек: зачет предоплаты при передаче товара ---
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SettlementReceiptError {
+    #[error("платеж в статусе {0:?}, чек зачета предоплаты возможен только для succeeded")]
+    PaymentNotSucceeded(PaymentStatus),
+    #[error("в первом чеке нет данных покупателя, а для /receipts они обязательны")]
+    MissingCustomer,
+    #[error("позиция {item}: признак способа расчета {mode} не является полной предоплатой")]
+    NotFullPrepayment { item: usize, mode: PaymentMode },
+    // Например, после частичного подтверждения сумма платежа меньше суммы первого чека
+    #[error("сумма позиций первого чека {items_total} не совпадает с суммой платежа {expected}")]
+    ReceiptMismatch {
+        expected: String,
+        items_total: String,
+    },
+    // Нарушения в самом чеке зачета, кроме расхождения суммы
+    #[error("чек зачета предоплаты не прошел проверку: {0}")]
+    InvalidReceipt(ReceiptValidationError),
+}
+
+impl CreateReceiptRequest {
+    // Чек полного расчета для платежа, по которому был пробит чек полной предоплаты.
+    // Позиции переносятся из первого чека с payment_mode = full_payment, а вся сумма
+    // платежа засчитывается как ранее внесенная предоплата. Сумма позиций первого чека
+    // должна совпадать с суммой платежа, иначе YooKassa отклонит чек.
+    pub fn final_settlement(
+        payment: &Payment,
+        prepayment_receipt: &Receipt,
+    ) -> Result<Self, SettlementReceiptError> {
+        if payment.status != PaymentStatus::Succeeded {
+            return Err(SettlementReceiptError::PaymentNotSucceeded(
+                payment.status.clone(),
+            ));
+        }
+        let customer = prepayment_receipt
+            .customer
+            .clone()
+            .ok_or(SettlementReceiptError::MissingCustomer)?;
+
+        let mut items = Vec::with_capacity(prepayment_receipt.items.len());
+        for (index, item) in prepayment_receipt.items.iter().enumerate() {
+            // Если payment_mode не указан, YooKassa считает его full_prepayment
+            match &item.payment_mode {
+                None | Some(PaymentMode::FullPrepayment) => {}
+                Some(mode) => {
+                    return Err(SettlementReceiptError::NotFullPrepayment {
+                        item: index,
+                        mode: mode.clone(),
+                    });
+                }
+            }
+            let mut item = item.clone();
+            item.payment_mode = Some(PaymentMode::FullPayment);
+            items.push(item);
+        }
+
+        let receipt = Receipt {
+            customer: Some(customer.clone()),
+            items,
+            tax_system_code: prepayment_receipt.tax_system_code,
+            receipt_industry_details: prepayment_receipt.receipt_industry_details.clone(),
+            receipt_operational_details: None,
+        };
+        if let Err(error) = receipt.validate_against(&payment.amount) {
+            let (mismatch, violations): (Vec<_>, Vec<_>) = error
+                .violations
+                .into_iter()
+                .partition(|violation| matches!(violation, ReceiptViolation::TotalMismatch { .. }));
+            if let Some(ReceiptViolation::TotalMismatch {
+                expected,
+                items_total,
+            }) = mismatch.into_iter().next()
+            {
+                return Err(SettlementReceiptError::ReceiptMismatch {
+                    expected,
+                    items_total,
+                });
+            }
+            return Err(SettlementReceiptError::InvalidReceipt(
+                ReceiptValidationError { violations },
+            ));
+        }
+
+        Ok(CreateReceiptRequest {
+            receipt_type: ReceiptType::Payment,
+            payment_id: Some(payment.id.clone()),
+            refund_id: None,
+            customer,
+            items: receipt.items,
+            send: true,
+            tax_system_code: prepayment_receipt.tax_system_code,
+            receipt_industry_details: prepayment_receipt.receipt_industry_details.clone(),
+            receipt_operational_details: None,
+            settlements: vec![Settlement {
+                settlement_type: SettlementType::Prepayment,
+                amount: payment.amount.clone(),
+            }],
+            on_behalf_of: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(price: &str, quantity: &str) -> ReceiptItem {
+        serde_json::from_value(serde_json::json!({
+            "description": "Товар",
+            "quantity": quantity,
+            "amount": { "value": price, "currency": "RUB" },
+            "vat_code": 1,
+        }))
+        .unwrap()
+    }
+
+    fn amount(value: &str) -> Amount {
+        Amount {
+            value: value.to_string(),
+            currency: "RUB".to_string(),
+        }
+    }
+
+    // Чек со скидкой: сумма позиций ровно total - discount, количество в каждой
+    // позиции сохраняется, строки после разбиения с ненулевым количеством
+    fn assert_built(items: &[(&str, &str)], discount: &str) -> Receipt {
+        let mut builder = ReceiptBuilder::new("RUB").discount(amount(discount));
+        let mut total = 0;
+        for (price, quantity) in items {
+            let item = item(price, quantity);
+            total += item.total_minor_units().unwrap();
+            builder = builder.item(item);
+        }
+        let receipt = builder.build().unwrap();
+
+        let receipt_total: i64 = receipt
+            .items
+            .iter()
+            .map(|item| item.total_minor_units().unwrap())
+            .sum();
+        assert_eq!(
+            receipt_total,
+            total - amount(discount).to_minor_units().unwrap()
+        );
+        let quantity: i64 = receipt
+            .items
+            .iter()
+            .map(|item| parse_quantity_thousandths(&item.quantity).unwrap())
+            .sum();
+        let expected_quantity: i64 = items
+            .iter()
+            .map(|(_, quantity)| parse_quantity_thousandths(quantity).unwrap())
+            .sum();
+        assert_eq!(quantity, expected_quantity);
+        assert!(receipt.items.iter().all(|item| {
+            parse_quantity_thousandths(&item.quantity).unwrap() > 0
+                && item.amount.to_minor_units().unwrap() >= 0
+        }));
+        receipt
+    }
+
+    #[test]
+    fn integer_quantities_with_remainder() {
+        let receipt = assert_built(&[("100.00", "3"), ("50.00", "1"), ("33.33", "7")], "10.00");
+        // 100.00 × 3 после скидки не делится на 3 - позиция разбита на две
+        assert!(receipt.items.len() > 3);
+        assert_built(&[("0.01", "3"), ("0.01", "3")], "0.01");
+        assert_built(&[("1.00", "3")], "0.01");
+    }
+
+    #[test]
+    fn fractional_quantities_above_one() {
+        assert_built(&[("99.99", "2.5"), ("10.01", "1.333")], "7.77");
+        assert_built(&[("123.45", "1.001"), ("0.99", "12.125")], "0.03");
+        assert_built(&[("10.00", "0.333"), ("15.50", "2.75")], "1.00");
+    }
+
+    #[test]
+    fn zero_priced_item() {
+        let receipt = assert_built(&[("0.00", "1"), ("100.00", "2")], "33.33");
+        assert_eq!(receipt.items[0].amount.value, "0.00");
+    }
+
+    #[test]
+    fn discount_equal_to_total() {
+        let receipt = assert_built(&[("100.00", "3"), ("12.34", "1.5")], "318.51");
+        assert!(receipt.items.iter().all(|item| item.amount.value == "0.00"));
+    }
+
+    #[test]
+    fn discount_exceeding_total_is_rejected() {
+        let result = ReceiptBuilder::new("RUB")
+            .item(item("10.00", "1"))
+            .discount(amount("10.01"))
+            .build();
+        assert!(matches!(
+            result,
+            Err(ReceiptBuildError::DiscountExceedsTotal { .. })
+        ));
+    }
+
+    fn payment(amount: &str) -> Payment {
+        serde_json::from_value(serde_json::json!({
+            "id": "2d9a2c5d-000f-5000-9000-1b68e7b15f3f",
+            "status": "succeeded",
+            "amount": { "value": amount, "currency": "RUB" },
+            "recipient": { "account_id": "100500", "gateway_id": "100700" },
+            "created_at": "2024-01-01T12:00:00.000Z",
+            "test": true,
+            "paid": true,
+            "refundable": true,
+        }))
+        .unwrap()
+    }
+
+    fn prepayment_receipt() -> Receipt {
+        let mut receipt = ReceiptBuilder::new("RUB")
+            .item(item("100.00", "2"))
+            .item(item("50.00", "1"))
+            .build()
+            .unwrap();
+        receipt.customer = Some(crate::ReceiptCustomer {
+            full_name: None,
+            inn: None,
+            email: Some("user@example.com".to_string()),
+            phone: None,
+        });
+        receipt
+    }
+
+    #[test]
+    fn final_settlement_matches_payment() {
+        let request =
+            CreateReceiptRequest::final_settlement(&payment("250.00"), &prepayment_receipt())
+                .unwrap();
+        assert_eq!(request.settlements[0].amount.value, "250.00");
+        assert!(
+            request
+                .items
+                .iter()
+                .all(|item| item.payment_mode == Some(PaymentMode::FullPayment))
+        );
+    }
+
+    #[test]
+    fn final_settlement_rejects_partial_capture() {
+        let result =
+            CreateReceiptRequest::final_settlement(&payment("200.00"), &prepayment_receipt());
+        assert_eq!(
+            result.unwrap_err(),
+            SettlementReceiptError::ReceiptMismatch {
+                expected: "200.00".to_string(),
+                items_total: "250.00".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn final_settlement_reports_receipt_violations_separately() {
+        let mut receipt = prepayment_receipt();
+        receipt.items[1].description = " ".to_string();
+        let result = CreateReceiptRequest::final_settlement(&payment("250.00"), &receipt);
+        let Err(SettlementReceiptError::InvalidReceipt(error)) = result else {
+            panic!("ожидалась ошибка InvalidReceipt: {result:?}");
+        };
+        assert_eq!(
+            error.violations,
+            [ReceiptViolation::EmptyDescription { item: 1 }]
+        );
+    }
+
+    #[test]
+    fn inn_checksum() {
+        assert!(is_valid_inn("7736207543")); // ИНН юрлица
+        assert!(is_valid_inn("500100732259")); // ИНН физлица
+        assert!(!is_valid_inn("7736207544"));
+        assert!(!is_valid_inn("500100732258"));
+        assert!(!is_valid_inn("500100732269"));
+        assert!(!is_valid_inn("773620754"));
+        assert!(!is_valid_inn("77362075430"));
+        assert!(!is_valid_inn("77362O7543"));
+        assert!(!is_valid_inn(""));
+    }
+
+    #[test]
+    fn distribute_proportionally_preserves_amount() {
+        let weights = [30000, 5000, 23331, 0, 1];
+        for amount in [0, 1, 7, 999, 12345, 58332] {
+            let shares = distribute_proportionally(amount, &weights);
+            assert_eq!(shares.iter().sum::<i64>(), amount);
+            assert_eq!(shares[3], 0);
+        }
+        // Равные остатки - копейка достается позиции, добавленной раньше
+        assert_eq!(distribute_proportionally(1, &[100, 100]), vec![1, 0]);
+        assert_eq!(distribute_proportionally(5, &[0, 0]), vec![0, 0]);
+    }
+
+    #[test]
+    fn split_line_is_exact() {
+        for quantity in [
+            125, 500, 999, 1000, 1001, 1333, 1500, 2000, 2750, 3000, 12125,
+        ] {
+            for line_total in 0..=2000 {
+                let parts = split_line(line_total, quantity).unwrap();
+                let total: i64 = parts
+                    .iter()
+                    .map(|(quantity, price)| round_thousandths(price * quantity))
+                    .sum();
+                assert_eq!(total, line_total, "quantity {quantity}");
+                assert_eq!(parts.iter().map(|(q, _)| q).sum::<i64>(), quantity);
+                assert!(parts.iter().all(|(q, price)| *q > 0 && *price >= 0));
+            }
+        }
+    }
+
+    #[test]
+    fn min_price_for_total_is_minimal() {
+        for quantity in [1, 125, 999, 1000, 1333, 2500] {
+            for total in 1..=500 {
+                let price = min_price_for_total(total, quantity).unwrap();
+                assert!(round_thousandths(price * quantity) >= total);
+                assert!(round_thousandths((price - 1) * quantity) < total);
+            }
+        }
+        assert_eq!(min_price_for_total(0, 1000), Some(0));
+        assert_eq!(min_price_for_total(i64::MAX / 100, 1500), None);
+    }
+
+    #[test]
+    fn build_rejects_overflowing_amounts() {
+        // Цена в копейках × количество в тысячных не помещается в i64
+        let result = ReceiptBuilder::new("RUB")
+            .item(item("1.00", "1"))
+            .item(item("90000000000000.00", "1000"))
+            .build();
+        assert_eq!(
+            result.unwrap_err(),
+            ReceiptBuildError::AmountOverflow { item: 1 }
+        );
+
+        // Каждая позиция помещается, а сумма заказа - нет
+        let mut builder = ReceiptBuilder::new("RUB");
+        for _ in 0..1025 {
+            builder = builder.item(item("90000000000000.00", "1"));
+        }
+        assert_eq!(
+            builder.build().unwrap_err(),
+            ReceiptBuildError::AmountOverflow { item: 1024 }
+        );
+    }
+
+    #[test]
+    fn build_rejects_too_many_items_after_split() {
+        // 60 позиций по 3 шт.: скидка в копейку на каждую разбивает их на две строки
+        let mut builder = ReceiptBuilder::new("RUB").discount(amount("0.60"));
+        for _ in 0..60 {
+            builder = builder.item(item("1.00", "3"));
+        }
+        assert_eq!(
+            builder.build().unwrap_err(),
+            ReceiptBuildError::TooManyItems(120)
+        );
+    }
+
+    fn round_trip<T>(wire: serde_json::Value, expected: T)
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned + PartialEq + std::fmt::Debug,
+    {
+        let parsed: T = serde_json::from_value(wire.clone()).unwrap();
+        assert_eq!(parsed, expected);
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), wire);
+    }
+
+    #[test]
+    fn enums_round_trip_known_and_unknown_values() {
+        round_trip(serde_json::json!(4), VatCode::Vat20);
+        round_trip(serde_json::json!(12), VatCode::Vat22Calculated);
+        round_trip(serde_json::json!(99), VatCode::Unknown(99));
+        round_trip(serde_json::json!(6), TaxSystemCode::Patent);
+        round_trip(serde_json::json!(7), TaxSystemCode::Unknown(7));
+        round_trip(serde_json::json!("full_payment"), PaymentMode::FullPayment);
+        round_trip(
+            serde_json::json!("installment"),
+            PaymentMode::Unknown("installment".to_string()),
+        );
+        round_trip(
+            serde_json::json!("marked_excise"),
+            PaymentSubject::MarkedExcise,
+        );
+        round_trip(
+            serde_json::json!("crypto_asset"),
+            PaymentSubject::Unknown("crypto_asset".to_string()),
+        );
+        round_trip(serde_json::json!("commissioner"), AgentType::Commissioner);
+        round_trip(
+            serde_json::json!("marketplace"),
+            AgentType::Unknown("marketplace".to_string()),
+        );
+
+        assert!(VatCode::from(99).is_unknown());
+        assert!(!PaymentSubject::from("service").is_unknown());
+    }
+
+    #[test]
+    fn receipt_item_keeps_unknown_values() {
+        let wire = serde_json::json!({
+            "description": "Товар",
+            "quantity": "1",
+            "amount": { "value": "100.00", "currency": "RUB" },
+            "vat_code": 13,
+            "payment_mode": "installment",
+            "payment_subject": "crypto_asset",
+            "agent_type": "marketplace",
+        });
+        let item: ReceiptItem = serde_json::from_value(wire.clone()).unwrap();
+        assert_eq!(item.vat_code, VatCode::Unknown(13));
+        assert_eq!(
+            item.payment_subject,
+            Some(PaymentSubject::Unknown("crypto_asset".to_string()))
+        );
+        assert_eq!(serde_json::to_value(&item).unwrap(), wire);
+    }
+}
@@ -0,0 +1,146 @@
+// --- Тестовые банковские карты YooKassa ---
+// В тестовом магазине исход платежа определяется номером карты. Таблица повторяет
+// эти соответствия, чтобы сценарии отказов можно было проверять без сети
+// (см. mock::MockServer с feature "mock").
+
+use crate::{CancellationParty, CancellationReason, CardData};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CardScenario {
+    Success,                     // Оплата проходит без 3-D Secure
+    ThreeDSecure,                // Оплата проходит после подтверждения 3-D Secure
+    Decline(CancellationReason), // Платеж отменяется с указанной причиной
+}
+
+#[derive(Debug, Clone)]
+pub struct SandboxCard {
+    pub number: &'static str,
+    pub card_type: &'static str, // Как в CardDetails.card_type
+    pub scenario: CardScenario,
+}
+
+impl CardScenario {
+    // Кто отменяет платеж при отказе
+    pub fn cancellation_party(&self) -> Option<CancellationParty> {
+        match self {
+            CardScenario::Decline(CancellationReason::FraudSuspected) => {
+                Some(CancellationParty::YooMoney)
+            }
+            CardScenario::Decline(_) => Some(CancellationParty::PaymentNetwork),
+            _ => None,
+        }
+    }
+}
+
+pub fn sandbox_cards() -> Vec<SandboxCard> {
+    use CancellationReason::*;
+    use CardScenario::*;
+
+    let card = |number, card_type, scenario| SandboxCard {
+        number,
+        card_type,
+        scenario,
+    };
+    vec![
+        card("5555555555554477", "MasterCard", Success),
+        card("5555555555554444", "MasterCard", ThreeDSecure),
+        card("6759649826438453", "Maestro", ThreeDSecure),
+        card("4793128161644804", "Visa", Success),
+        card("4111111111111111", "Visa", ThreeDSecure),
+        card("2202474301322987", "Mir", ThreeDSecure),
+        card("370000000000002", "AmericanExpress", ThreeDSecure),
+        card(
+            "5555555555555599",
+            "MasterCard",
+            Decline(ThreeDSecureFailed),
+        ),
+        card("5555555555555508", "MasterCard", Decline(CallIssuer)),
+        card("5555555555555557", "MasterCard", Decline(CardExpired)),
+        card("5555555555555565", "MasterCard", Decline(CountryForbidden)),
+        card("5555555555555524", "MasterCard", Decline(FraudSuspected)),
+        card("5555555555555516", "MasterCard", Decline(GeneralDecline)),
+        card(
+            "5555555555555576",
+            "MasterCard",
+            Decline(IdentificationRequired),
+        ),
+        card("5555555555555540", "MasterCard", Decline(InsufficientFunds)),
+        card("5555555555555573", "MasterCard", Decline(InvalidCardNumber)),
+        card("5555555555555581", "MasterCard", Decline(InvalidCsc)),
+        card("4000000000000119", "Visa", Decline(IssuerUnavailable)),
+        card(
+            "5555555555555532",
+            "MasterCard",
+            Decline(PaymentMethodLimitExceeded),
+        ),
+        card(
+            "5555555555555590",
+            "MasterCard",
+            Decline(PaymentMethodRestricted),
+        ),
+    ]
+}
+
+pub fn find_sandbox_card(number: &str) -> Option<SandboxCard> {
+    let number = normalize_card_number(number);
+    sandbox_cards()
+        .into_iter()
+        .find(|card| card.number == number)
+}
+
+// Исход платежа картой в тестовом магазине. Карты не из таблицы проходят без 3-D Secure,
+// если номер корректен по алгоритму Луна, иначе платеж отклоняется с invalid_card_number.
+pub fn card_scenario(card: &CardData) -> CardScenario {
+    if let Some(sandbox_card) = find_sandbox_card(card.number.expose_secret()) {
+        return sandbox_card.scenario;
+    }
+    if is_luhn_valid(&normalize_card_number(card.number.expose_secret())) {
+        CardScenario::Success
+    } else {
+        CardScenario::Decline(CancellationReason::InvalidCardNumber)
+    }
+}
+
+// Тип карты по первой цифре номера (для CardDetails.card_type)
+pub fn card_type(number: &str) -> &'static str {
+    let number = normalize_card_number(number);
+    if let Some(card) = find_sandbox_card(&number) {
+        return card.card_type;
+    }
+    match number.as_bytes().first() {
+        Some(b'2') => "Mir",
+        Some(b'3') => "AmericanExpress",
+        Some(b'4') => "Visa",
+        Some(b'5') => "MasterCard",
+        Some(b'6') => "Maestro",
+        _ => "Unknown",
+    }
+}
+
+fn normalize_card_number(number: &str) -> String {
+    number
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect()
+}
+
+fn is_luhn_valid(number: &str) -> bool {
+    if number.len() < 12 || !number.bytes().all(|b| b.is_ascii_digit()) {
+        return false;
+    }
+    let sum: u32 = number
+        .bytes()
+        .rev()
+        .enumerate()
+        .map(|(index, b)| {
+            let digit = u32::from(b - b'0');
+            if index % 2 == 1 {
+                let doubled = digit * 2;
+                if doubled > 9 { doubled - 9 } else { doubled }
+            } else {
+                digit
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
@@ -0,0 +1,123 @@
+// --- Секреты: ключи API, OAuth-токены, данные карт ---
+// SecretString не печатает значение в Debug/Display и затирает память при удалении.
+// Значение доступно только явно, через expose_secret.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use zeroize::Zeroize;
+
+const REDACTED: &str = "***";
+
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct SecretString(String);
+
+impl SecretString {
+    pub fn new(secret: impl Into<String>) -> Self {
+        SecretString(secret.into())
+    }
+
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl std::fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SecretString({REDACTED})")
+    }
+}
+
+impl std::fmt::Display for SecretString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(secret: String) -> Self {
+        SecretString(secret)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(secret: &str) -> Self {
+        SecretString(secret.to_string())
+    }
+}
+
+// Сериализуется как есть: секрет уходит в тело запроса к API
+impl Serialize for SecretString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for SecretString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(SecretString)
+    }
+}
+
+// Номер карты для логов. В 16-19 цифрах после первых 6 и последних 4 остается
+// не меньше 6 скрытых; в номерах короче первые 6 + последние 4 раскрыли бы почти все
+// цифры, поэтому там показываются только последние 4.
+const MASK_BIN_MIN_DIGITS: usize = 16;
+const MASK_MIN_DIGITS: usize = 10;
+
+pub fn mask_card_number(number: &str) -> String {
+    let digits: Vec<char> = number.chars().filter(char::is_ascii_digit).collect();
+    if digits.len() < MASK_MIN_DIGITS {
+        return REDACTED.to_string();
+    }
+    let shown_first = if digits.len() >= MASK_BIN_MIN_DIGITS {
+        6
+    } else {
+        0
+    };
+    let (first, rest) = digits.split_at(shown_first);
+    let (middle, last4) = rest.split_at(rest.len() - 4);
+    format!(
+        "{}{}{}",
+        first.iter().collect::<String>(),
+        "*".repeat(middle.len()),
+        last4.iter().collect::<String>()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mask_short_numbers_keep_last4_only() {
+        assert_eq!(mask_card_number("123456789"), REDACTED);
+        assert_eq!(mask_card_number("1234567890"), "******7890");
+        assert_eq!(mask_card_number("220012345678901"), "***********8901");
+    }
+
+    #[test]
+    fn mask_16_and_19_digit_numbers() {
+        assert_eq!(mask_card_number("4793 1281 6164 4804"), "479312******4804");
+        assert_eq!(
+            mask_card_number("2200123456789012345"),
+            "220012*********2345"
+        );
+    }
+
+    #[test]
+    fn secret_string_is_not_printed() {
+        let secret = SecretString::new("test_secret");
+        assert_eq!(format!("{secret:?}"), "SecretString(***)");
+        assert_eq!(secret.to_string(), REDACTED);
+        assert_eq!(secret.expose_secret(), "test_secret");
+    }
+}
@@ -0,0 +1,170 @@
+// --- Наблюдаемость запросов к API ---
+// С feature "tracing" каждый вызов API получает span "yookassa.request" с полями
+// endpoint, method, status, error_code, payment_id, idempotency_key, latency_ms, attempt,
+// а каждая попытка - событие внутри него. Отправка запроса (включая повторы) выполняется
+// внутри span, поэтому события reqwest/hyper и своего транспорта привязаны к нему.
+// Заголовки (Authorization) и тела запросов
+// не записываются, поэтому ключи и данные карт в трассировку не попадают.
+// Без feature все методы пустые.
+
+use reqwest::Method;
+use std::future::Future;
+use std::time::Duration;
+#[cfg(feature = "tracing")]
+use std::time::Instant;
+#[cfg(feature = "tracing")]
+use tracing::Instrument;
+
+use crate::YooKassaError;
+use crate::transport::HttpResponse;
+
+pub(crate) struct RequestTrace {
+    #[cfg(feature = "tracing")]
+    started: Instant,
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+    #[cfg(feature = "tracing")]
+    payment_id_source: PaymentIdSource,
+}
+
+// Откуда взять payment_id, если его нет в пути
+#[cfg(feature = "tracing")]
+enum PaymentIdSource {
+    Path,              // payments/{id}/... - уже записан при старте
+    ResponseId,        // POST payments, ответ - сам платеж
+    ResponsePaymentId, // Возвраты и чеки ссылаются на платеж через payment_id
+}
+
+#[cfg(feature = "tracing")]
+impl RequestTrace {
+    pub(crate) fn start(method: &Method, endpoint: &str, idempotency_key: Option<&str>) -> Self {
+        // Путь без query: в query бывают фильтры, но не идентификаторы
+        let path = endpoint.split('?').next().unwrap_or_default();
+        let payment_id = payment_id_from_endpoint(path);
+        let payment_id_source = match payment_id {
+            Some(_) => PaymentIdSource::Path,
+            None if path == "payments" => PaymentIdSource::ResponseId,
+            None => PaymentIdSource::ResponsePaymentId,
+        };
+        let span = tracing::info_span!(
+            "yookassa.request",
+            endpoint = path,
+            method = %method,
+            idempotency_key = idempotency_key,
+            payment_id,
+            status = tracing::field::Empty,
+            error_code = tracing::field::Empty,
+            latency_ms = tracing::field::Empty,
+            attempt = tracing::field::Empty,
+        );
+        RequestTrace {
+            started: Instant::now(),
+            span,
+            payment_id_source,
+        }
+    }
+
+    // Future выполняется внутри span запроса
+    pub(crate) fn instrument<F: Future>(&self, future: F) -> impl Future<Output = F::Output> {
+        future.instrument(self.span.clone())
+    }
+
+    // attempt считается с 1; вызывается внутри instrument
+    pub(crate) fn attempt_finished(
+        &self,
+        attempt: u32,
+        latency: Duration,
+        result: &Result<HttpResponse, YooKassaError>,
+    ) {
+        let latency_ms = latency.as_millis() as u64;
+        match result {
+            Ok(response) => {
+                tracing::debug!(
+                    attempt,
+                    status = response.status,
+                    latency_ms,
+                    "попытка завершена"
+                )
+            }
+            Err(error) => tracing::warn!(attempt, latency_ms, error = %error, "ошибка транспорта"),
+        }
+    }
+
+    pub(crate) fn finish(&self, attempts: u32, result: &Result<HttpResponse, YooKassaError>) {
+        let span = &self.span;
+        span.record("attempt", attempts);
+        span.record("latency_ms", self.started.elapsed().as_millis() as u64);
+        let response = match result {
+            Ok(response) => response,
+            Err(error) => {
+                span.in_scope(|| tracing::error!(error = %error, "запрос не выполнен"));
+                return;
+            }
+        };
+        span.record("status", response.status);
+        let summary = ResponseSummary::parse(&response.body);
+        if response.is_success() {
+            let payment_id = match self.payment_id_source {
+                PaymentIdSource::Path => None,
+                PaymentIdSource::ResponseId => summary.id,
+                PaymentIdSource::ResponsePaymentId => summary.payment_id,
+            };
+            if let Some(payment_id) = payment_id {
+                span.record("payment_id", payment_id.as_str());
+            }
+            span.in_scope(|| tracing::info!("запрос выполнен"));
+        } else {
+            if let Some(code) = &summary.code {
+                span.record("error_code", code.as_str());
+            }
+            span.in_scope(|| tracing::warn!("API вернул ошибку"));
+        }
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+impl RequestTrace {
+    pub(crate) fn start(_method: &Method, _endpoint: &str, _idempotency_key: Option<&str>) -> Self {
+        RequestTrace {}
+    }
+
+    pub(crate) fn instrument<F: Future>(&self, future: F) -> impl Future<Output = F::Output> {
+        future
+    }
+
+    pub(crate) fn attempt_finished(
+        &self,
+        _attempt: u32,
+        _latency: Duration,
+        _result: &Result<HttpResponse, YooKassaError>,
+    ) {
+    }
+
+    pub(crate) fn finish(&self, _attempts: u32, _result: &Result<HttpResponse, YooKassaError>) {}
+}
+
+// Идентификатор платежа из пути вида payments/{id} и payments/{id}/capture
+#[cfg(feature = "tracing")]
+fn payment_id_from_endpoint(path: &str) -> Option<&str> {
+    let mut segments = path.split('/');
+    match (segments.next(), segments.next()) {
+        (Some("payments"), Some(id)) if !id.is_empty() => Some(id),
+        _ => None,
+    }
+}
+
+// Только поля, нужные для трассировки; остальное тело не разбирается и не логируется
+#[cfg(feature = "tracing")]
+#[derive(serde::Deserialize, Default)]
+struct ResponseSummary {
+    id: Option<String>,
+    payment_id: Option<String>,
+    code: Option<String>, // Код ошибки API
+}
+
+#[cfg(feature = "tracing")]
+impl ResponseSummary {
+    fn parse(body: &str) -> Self {
+        serde_json::from_str(body).unwrap_or_default()
+    }
+}
@@ -0,0 +1,122 @@
+// --- HTTP транспорт клиента ---
+// YooKassaClient формирует HttpRequest (URL, заголовки с авторизацией, JSON тело)
+// и передает его транспорту. По умолчанию это reqwest::Client, но можно подставить
+// свой клиент (общий пул соединений, прокси, TLS), hyper или тестовую заглушку.
+
+use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Client as ReqwestClient, Method};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{SecretString, YooKassaError};
+
+pub type TransportFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<HttpResponse, YooKassaError>> + Send + 'a>>;
+
+pub trait HttpTransport: Send + Sync {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_>;
+}
+
+impl HttpTransport for ReqwestClient {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        Box::pin(send_with_reqwest(self, request))
+    }
+}
+
+impl<T: HttpTransport + ?Sized> HttpTransport for Arc<T> {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        (**self).send(request)
+    }
+}
+
+// Клиент по умолчанию для YooKassaClient и OAuthClient
+pub(crate) fn default_reqwest_client() -> Result<ReqwestClient, YooKassaError> {
+    Ok(ReqwestClient::builder()
+        .timeout(Duration::from_secs(30))
+        .build()?)
+}
+
+// Сетевая ошибка своего транспорта (соединение, таймаут). Такие ошибки, как и сетевые
+// ошибки reqwest, повторяются по RetryPolicy и считаются неудачей для circuit breaker;
+// остальные ошибки транспорта возвращаются сразу.
+#[derive(Debug, thiserror::Error)]
+#[error("сетевая ошибка: {0}")]
+pub struct NetworkError(pub YooKassaError);
+
+pub(crate) fn is_network_error(error: &YooKassaError) -> bool {
+    if error.is::<NetworkError>() {
+        return true;
+    }
+    error
+        .downcast_ref::<reqwest::Error>()
+        .is_some_and(|error| error.is_timeout() || error.is_connect() || error.is_request())
+}
+
+#[derive(Clone)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: Vec<(String, String)>, // Idempotence-Key, Content-Type, ...
+    // Значение заголовка Authorization; хранится отдельно от headers, чтобы копии
+    // ключа (в том числе в повторах) затирались при удалении запроса
+    pub authorization: Option<SecretString>,
+    pub body: Option<Vec<u8>>,     // JSON тело запроса
+    pub timeout: Option<Duration>, // Таймаут на весь запрос, зависит от класса операции
+}
+
+impl HttpRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        if name.eq_ignore_ascii_case(AUTHORIZATION.as_str()) {
+            return self.authorization.as_ref().map(SecretString::expose_secret);
+        }
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl HttpResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+async fn send_with_reqwest(
+    client: &ReqwestClient,
+    request: HttpRequest,
+) -> Result<HttpResponse, YooKassaError> {
+    let mut headers = HeaderMap::new();
+    for (name, value) in &request.headers {
+        headers.insert(
+            HeaderName::from_bytes(name.as_bytes())?,
+            HeaderValue::from_str(value)?,
+        );
+    }
+    if let Some(authorization) = &request.authorization {
+        let mut value = HeaderValue::from_str(authorization.expose_secret())?;
+        value.set_sensitive(true);
+        headers.insert(AUTHORIZATION, value);
+    }
+
+    let mut request_builder = client.request(request.method, request.url).headers(headers);
+    if let Some(body) = request.body {
+        request_builder = request_builder.body(body);
+    }
+    if let Some(timeout) = request.timeout {
+        request_builder = request_builder.timeout(timeout);
+    }
+
+    let response = request_builder.send().await?;
+    let status = response.status().as_u16();
+    let body = response.text().await?;
+    Ok(HttpResponse { status, body })
+}
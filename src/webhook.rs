@@ -0,0 +1,86 @@
+// --- Входящие уведомления (webhook) YooKassa ---
+// YooKassa присылает POST с JSON вида {"type": "notification", "event": "...", "object": {...}},
+// где object - платеж или возврат в том же формате, что и ответы API.
+
+use serde::{Deserialize, Serialize};
+
+use crate::metrics_sink::MetricsSink;
+use crate::{Payment, Refund, YooKassaError};
+
+api_enum! {
+    // Событие, о котором уведомляет YooKassa
+    pub enum NotificationEvent {
+        PaymentWaitingForCapture => "payment.waiting_for_capture",
+        PaymentSucceeded => "payment.succeeded",
+        PaymentCanceled => "payment.canceled",
+        RefundSucceeded => "refund.succeeded",
+        PayoutSucceeded => "payout.succeeded",
+        PayoutCanceled => "payout.canceled",
+        DealClosed => "deal.closed",
+    }
+}
+
+impl NotificationEvent {
+    pub fn is_payment_event(&self) -> bool {
+        self.as_str().starts_with("payment.")
+    }
+
+    pub fn is_refund_event(&self) -> bool {
+        self.as_str().starts_with("refund.")
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WebhookNotification {
+    #[serde(rename = "type")]
+    pub notification_type: String, // Всегда "notification"
+    pub event: NotificationEvent,
+    pub object: serde_json::Value, // Объект события, см. payment() и refund()
+    #[serde(flatten, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>, // Поля, неизвестные этой версии библиотеки
+}
+
+impl WebhookNotification {
+    pub fn from_json(body: &str) -> Result<Self, YooKassaError> {
+        Ok(serde_json::from_str(body)?)
+    }
+
+    // Разбор с учетом в метриках (webhook_received). Единственное место, где уведомление
+    // попадает в метрики: YooKassaClient::parse_notification и
+    // YooKassaClientPool::parse_notification вызывают этот метод.
+    pub fn from_json_with_metrics(
+        body: &str,
+        metrics: &dyn MetricsSink,
+    ) -> Result<Self, YooKassaError> {
+        let notification = Self::from_json(body)?;
+        metrics.webhook_received(&notification.event);
+        Ok(notification)
+    }
+
+    // Платеж из уведомления payment.*; None для остальных событий
+    pub fn payment(&self) -> Result<Option<Payment>, YooKassaError> {
+        if !self.event.is_payment_event() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_value(self.object.clone())?))
+    }
+
+    // Возврат из уведомления refund.*; None для остальных событий
+    pub fn refund(&self) -> Result<Option<Refund>, YooKassaError> {
+        if !self.event.is_refund_event() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_value(self.object.clone())?))
+    }
+
+    // Магазин, к которому относится объект (recipient.account_id платежа).
+    // У возвратов и выплат recipient нет.
+    pub fn account_id(&self) -> Option<&str> {
+        self.object.get("recipient")?.get("account_id")?.as_str()
+    }
+
+    // id объекта события (платежа, возврата, выплаты)
+    pub fn object_id(&self) -> Option<&str> {
+        self.object.get("id")?.as_str()
+    }
+}
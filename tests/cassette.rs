@@ -0,0 +1,70 @@
+// Запись кассеты против mock::MockServer и воспроизведение без сети (cargo test --features mock)
+#![cfg(feature = "mock")]
+
+use yookassa_rs::mock::MockServer;
+use yookassa_rs::{
+    Amount, CardData, Cassette, CreatePaymentRequest, PaymentMethodData, PaymentMethodType,
+    UnmatchedRequestError, YooKassaClient,
+};
+
+fn card_payment(value: &str, number: &str) -> CreatePaymentRequest {
+    CreatePaymentRequest {
+        amount: Amount::new(value, "RUB"),
+        payment_method_data: Some(PaymentMethodData {
+            payment_method_type: PaymentMethodType::BankCard,
+            card: Some(CardData {
+                number: number.into(),
+                expiry_year: "2030".to_string(),
+                expiry_month: "12".to_string(),
+                csc: Some("123".into()),
+                cardholder: None,
+            }),
+            login: None,
+            phone: None,
+        }),
+        capture: Some(true),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn record_then_replay_cassette() {
+    let path = std::env::temp_dir().join(format!("yookassa-{}.json", uuid::Uuid::new_v4()));
+
+    let server = MockServer::start().await.unwrap();
+    let recorder = server.client().unwrap().record_cassette(&path);
+    let created = recorder
+        .create_payment(&card_payment("250.00", "4793128161644804"))
+        .await
+        .unwrap();
+    let fetched = recorder.get_payment(&created.id).await.unwrap();
+    drop(server);
+
+    // Номер карты и CSC в кассету не попадают
+    let content = std::fs::read_to_string(&path).unwrap();
+    assert!(!content.contains("4793128161644804"));
+    let cassette = Cassette::load(&path).unwrap();
+    let card =
+        &cassette.interactions[0].request.body.as_ref().unwrap()["payment_method_data"]["card"];
+    assert_eq!(card["csc"], "***");
+
+    // Воспроизведение без сети: адрес не обслуживается, ответы берутся из кассеты
+    let replay = YooKassaClient::new("100500".to_string(), "test_mock_secret_key")
+        .unwrap()
+        .set_base_url("http://127.0.0.1:9/v3/".to_string())
+        .replay_cassette(&path)
+        .unwrap();
+    let replayed = replay
+        .create_payment(&card_payment("250.00", "4793128161644804"))
+        .await
+        .unwrap();
+    assert_eq!(replayed.id, created.id);
+    let replayed = replay.get_payment(&created.id).await.unwrap();
+    assert_eq!(replayed.status, fetched.status);
+
+    // Каждая запись воспроизводится один раз
+    let error = replay.get_payment(&created.id).await.unwrap_err();
+    assert!(error.is::<UnmatchedRequestError>());
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,51 @@
+// Circuit breaker вокруг запросов клиента (cargo test --features mock)
+#![cfg(feature = "mock")]
+
+use std::sync::Arc;
+
+use yookassa_rs::mock::{MockFailure, MockServer};
+use yookassa_rs::{
+    Cassette, CircuitBreaker, CircuitBreakerConfig, CircuitOpenError, CircuitState,
+    ReplayTransport, UnmatchedRequestError, YooKassaClient,
+};
+
+fn breaker() -> Arc<CircuitBreaker> {
+    Arc::new(CircuitBreaker::new(CircuitBreakerConfig {
+        minimum_requests: 1,
+        ..Default::default()
+    }))
+}
+
+#[tokio::test]
+async fn network_errors_open_circuit() {
+    let server = MockServer::start().await.unwrap();
+    let breaker = breaker();
+    let client = YooKassaClient::builder("100500".to_string(), "test_mock_secret_key")
+        .base_url(server.base_url())
+        .circuit_breaker(breaker.clone())
+        .build()
+        .unwrap();
+
+    server.fail_next(MockFailure::Disconnect);
+    assert!(client.get_me().await.is_err());
+    assert_eq!(breaker.state(), CircuitState::Open);
+
+    // Пока цепь разомкнута, запрос не доходит до сервера
+    let error = client.get_me().await.unwrap_err();
+    assert!(error.is::<CircuitOpenError>());
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn non_network_errors_keep_circuit_closed() {
+    // Запрос, которого нет в кассете, не считается сбоем API
+    let breaker = breaker();
+    let client = YooKassaClient::builder("100500".to_string(), "test_mock_secret_key")
+        .transport(ReplayTransport::new(Cassette::default()))
+        .circuit_breaker(breaker.clone())
+        .build()
+        .unwrap();
+    let error = client.get_me().await.unwrap_err();
+    assert!(error.is::<UnmatchedRequestError>());
+    assert_eq!(breaker.state(), CircuitState::Closed);
+}
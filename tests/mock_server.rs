@@ -0,0 +1,67 @@
+// Интеграционные тесты клиента против mock::MockServer (cargo test --features mock)
+#![cfg(feature = "mock")]
+
+use yookassa_rs::mock::MockServer;
+use yookassa_rs::{
+    Amount, ConfirmationRequest, ConfirmationType, CreatePaymentRequest, CreateRefundRequest,
+    PaymentStatus, RefundStatus,
+};
+
+fn redirect_payment(value: &str) -> CreatePaymentRequest {
+    CreatePaymentRequest {
+        amount: Amount::new(value, "RUB"),
+        description: Some("Заказ №1".to_string()),
+        confirmation: Some(ConfirmationRequest {
+            confirmation_type: ConfirmationType::Redirect,
+            return_url: "https://example.com/return".to_string(),
+            enforce: None,
+            locale: None,
+        }),
+        capture: Some(false),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn create_confirm_capture_refund() {
+    let server = MockServer::start().await.unwrap();
+    let client = server.client().unwrap();
+
+    let payment = client
+        .create_payment(&redirect_payment("1500.00"))
+        .await
+        .unwrap();
+    assert_eq!(payment.status, PaymentStatus::Pending);
+    assert!(payment.confirmation.unwrap().confirmation_url.is_some());
+
+    assert!(server.confirm_payment(&payment.id));
+    let payment = client.get_payment(&payment.id).await.unwrap();
+    assert_eq!(payment.status, PaymentStatus::WaitingForCapture);
+    assert!(payment.expires_at.is_some());
+
+    let payment = client.capture_payment(&payment.id, None).await.unwrap();
+    assert_eq!(payment.status, PaymentStatus::Succeeded);
+    assert!(payment.captured_at.is_some());
+
+    let refund = client
+        .create_refund(&CreateRefundRequest {
+            payment_id: payment.id.clone(),
+            amount: Amount::new("500.00", "RUB"),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(refund.status, RefundStatus::Succeeded);
+    let payment = client.get_payment(&payment.id).await.unwrap();
+    assert_eq!(payment.remaining_refundable().unwrap().value, "1000.00");
+
+    // Возврат больше остатка отклоняется, как в настоящем API
+    let result = client
+        .create_refund(&CreateRefundRequest {
+            payment_id: payment.id.clone(),
+            amount: Amount::new("1000.01", "RUB"),
+            ..Default::default()
+        })
+        .await;
+    assert!(result.is_err());
+}
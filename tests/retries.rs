@@ -0,0 +1,81 @@
+// Повторы запросов клиента против mock::MockServer (cargo test --features mock)
+#![cfg(feature = "mock")]
+
+use std::time::Duration;
+
+use yookassa_rs::mock::{MockFailure, MockServer};
+use yookassa_rs::{
+    Amount, ApiErrorCode, ConfirmationRequest, ConfirmationType, CreatePaymentRequest, RetryPolicy,
+    YooKassaClient,
+};
+
+fn redirect_payment(value: &str) -> CreatePaymentRequest {
+    CreatePaymentRequest {
+        amount: Amount::new(value, "RUB"),
+        description: Some("Заказ №1".to_string()),
+        confirmation: Some(ConfirmationRequest {
+            confirmation_type: ConfirmationType::Redirect,
+            return_url: "https://example.com/return".to_string(),
+            enforce: None,
+            locale: None,
+        }),
+        capture: Some(false),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn retry_replays_idempotent_response() {
+    let server = MockServer::start().await.unwrap();
+    let client = YooKassaClient::builder("100500".to_string(), "test_mock_secret_key")
+        .base_url(server.base_url())
+        .write_timeout(Duration::from_millis(200))
+        .retry_policy(
+            RetryPolicy::new(1).set_backoff(Duration::from_secs(1), Duration::from_secs(1)),
+        )
+        .build()
+        .unwrap();
+
+    // Первая попытка не дожидается ответа, но сервер успевает создать платеж;
+    // повтор с тем же ключом получает сохраненный ответ, а не второй платеж
+    server.fail_next(MockFailure::Delay(Duration::from_millis(500)));
+    let payment = client
+        .create_payment(&redirect_payment("100.00"))
+        .await
+        .unwrap();
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[0].idempotence_key.is_some());
+    assert_eq!(requests[0].idempotence_key, requests[1].idempotence_key);
+    let payments = client.list_payments(None).await.unwrap();
+    assert_eq!(payments.items.len(), 1);
+    assert_eq!(payments.items[0].id, payment.id);
+}
+
+#[tokio::test]
+async fn retries_network_and_server_errors() {
+    let retry_policy =
+        RetryPolicy::new(2).set_backoff(Duration::from_millis(10), Duration::from_millis(10));
+    let server = MockServer::start().await.unwrap();
+    let client = YooKassaClient::builder("100500".to_string(), "test_mock_secret_key")
+        .base_url(server.base_url())
+        .retry_policy(retry_policy)
+        .build()
+        .unwrap();
+    server.fail_next(MockFailure::Disconnect);
+    client.get_me().await.unwrap();
+    assert_eq!(server.requests().len(), 2);
+
+    // 5xx повторяется, а ошибка в запросе (4xx) - нет
+    server.fail_next(MockFailure::internal_server_error());
+    client.get_me().await.unwrap();
+    assert_eq!(server.requests().len(), 4);
+    server.fail_next(MockFailure::ApiError {
+        status: 400,
+        code: ApiErrorCode::InvalidRequest,
+        description: "Invalid request".to_string(),
+    });
+    assert!(client.get_me().await.is_err());
+    assert_eq!(server.requests().len(), 5);
+}
@@ -0,0 +1,52 @@
+// Тестовые карты песочницы против mock::MockServer (cargo test --features mock)
+#![cfg(feature = "mock")]
+
+use yookassa_rs::mock::MockServer;
+use yookassa_rs::sandbox::find_sandbox_card;
+use yookassa_rs::{
+    Amount, CancellationParty, CancellationReason, CardData, CreatePaymentRequest,
+    PaymentMethodData, PaymentMethodType, PaymentStatus,
+};
+
+fn card_payment(value: &str, number: &str) -> CreatePaymentRequest {
+    CreatePaymentRequest {
+        amount: Amount::new(value, "RUB"),
+        payment_method_data: Some(PaymentMethodData {
+            payment_method_type: PaymentMethodType::BankCard,
+            card: Some(CardData {
+                number: number.into(),
+                expiry_year: "2030".to_string(),
+                expiry_month: "12".to_string(),
+                csc: Some("123".into()),
+                cardholder: None,
+            }),
+            login: None,
+            phone: None,
+        }),
+        capture: Some(true),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn declined_sandbox_card() {
+    let server = MockServer::start().await.unwrap();
+    let client = server.client().unwrap();
+
+    let card = find_sandbox_card("5555 5555 5555 5540").unwrap();
+    let payment = client
+        .create_payment(&card_payment("100.00", card.number))
+        .await
+        .unwrap();
+    assert_eq!(payment.status, PaymentStatus::Canceled);
+    let details = payment.cancellation_details.unwrap();
+    assert_eq!(details.reason, CancellationReason::InsufficientFunds);
+    assert_eq!(details.party, CancellationParty::PaymentNetwork);
+
+    let payment = client
+        .create_payment(&card_payment("100.00", "4793128161644804"))
+        .await
+        .unwrap();
+    assert_eq!(payment.status, PaymentStatus::Succeeded);
+    assert_eq!(payment.payment_method.unwrap().card.unwrap().last4, "4804");
+}